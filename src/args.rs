@@ -0,0 +1,327 @@
+use std::{
+    env,
+    process,
+    fmt::Display,
+    str::FromStr
+};
+
+
+fn fail(message: &str) -> !
+{
+    eprintln!("{message}");
+    process::exit(1)
+}
+
+fn value_of<T>(name: &str, value: Option<String>) -> T
+where
+    T: FromStr,
+    T::Err: Display
+{
+    let value = value.unwrap_or_else(|| fail(&format!("{name} expects a value")));
+
+    value.parse().unwrap_or_else(|err| fail(&format!("invalid value for {name} ({value}): {err}")))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Size
+{
+    pub width: u32,
+    pub height: u32
+}
+
+impl FromStr for Size
+{
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err>
+    {
+        let (width, height) = s.split_once('x').ok_or_else(|| "expected WIDTHxHEIGHT".to_owned())?;
+
+        let parse = |x: &str| x.trim().parse::<u32>().map_err(|err| err.to_string());
+
+        Ok(Self{width: parse(width)?, height: parse(height)?})
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Position
+{
+    pub x: i32,
+    pub y: i32
+}
+
+impl FromStr for Position
+{
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err>
+    {
+        let (x, y) = s.split_once(',').ok_or_else(|| "expected X,Y".to_owned())?;
+
+        let parse = |x: &str| x.trim().parse::<i32>().map_err(|err| err.to_string());
+
+        Ok(Self{x: parse(x)?, y: parse(y)?})
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HexColor(pub [u8; 3]);
+
+impl FromStr for HexColor
+{
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err>
+    {
+        let s = s.trim_start_matches('#');
+
+        if s.len() != 6 || !s.is_ascii()
+        {
+            return Err("expected RRGGBB".to_owned());
+        }
+
+        let channel = |index: usize|
+        {
+            u8::from_str_radix(&s[index * 2..index * 2 + 2], 16).map_err(|err| err.to_string())
+        };
+
+        Ok(Self([channel(0)?, channel(1)?, channel(2)?]))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScaleMode
+{
+    Fit,
+    Fill,
+    Stretch
+}
+
+impl ScaleMode
+{
+    pub fn next(self) -> Self
+    {
+        match self
+        {
+            Self::Fit => Self::Fill,
+            Self::Fill => Self::Stretch,
+            Self::Stretch => Self::Fit
+        }
+    }
+}
+
+impl FromStr for ScaleMode
+{
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err>
+    {
+        match s
+        {
+            "fit" => Ok(Self::Fit),
+            "fill" => Ok(Self::Fill),
+            "stretch" => Ok(Self::Stretch),
+            _ => Err("expected fit, fill or stretch".to_owned())
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScaleQuality
+{
+    Nearest,
+    Linear,
+    Best
+}
+
+impl ScaleQuality
+{
+    pub fn hint_value(self) -> &'static str
+    {
+        match self
+        {
+            Self::Nearest => "nearest",
+            Self::Linear => "linear",
+            Self::Best => "best"
+        }
+    }
+}
+
+impl FromStr for ScaleQuality
+{
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err>
+    {
+        match s
+        {
+            "nearest" => Ok(Self::Nearest),
+            "linear" => Ok(Self::Linear),
+            "best" => Ok(Self::Best),
+            _ => Err("expected nearest, linear or best".to_owned())
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CameraList(pub Vec<u32>);
+
+impl FromStr for CameraList
+{
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err>
+    {
+        s.split(',').map(|x| x.trim().parse::<u32>().map_err(|err| err.to_string()))
+            .collect::<Result<Vec<_>, _>>()
+            .map(Self)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Args
+{
+    pub camera: Option<u32>,
+    pub resolution: Option<Size>,
+    pub fps: Option<u32>,
+    pub crosshair_color: Option<HexColor>,
+    pub target_brightness: Option<f64>,
+    pub brightness_range: Option<f64>,
+    pub brightness_hysteresis: Option<f64>,
+    pub fps_window: Option<usize>,
+    pub max_fps: Option<u32>,
+    pub borderless: bool,
+    pub motion_threshold: Option<f64>,
+    pub motion_snapshot: bool,
+    pub timelapse: Option<f64>,
+    pub verbose: bool,
+    pub burst_frames: Option<usize>,
+    pub max_record_frames: Option<usize>,
+    pub yuyv_decode: bool,
+    pub vignette_strength: Option<f64>,
+    pub k1: Option<f64>,
+    pub k2: Option<f64>,
+    pub list_cameras: bool,
+    pub stats_log: Option<String>,
+    pub cameras: Option<CameraList>,
+    pub window_of: Option<CameraList>,
+    pub mirror: Option<bool>,
+    pub flip_vertical: Option<bool>,
+    pub posterize_levels: Option<u32>,
+    pub serve: Option<u16>,
+    pub replay: Option<f64>,
+    pub zebra_threshold: Option<f64>,
+    pub no_resize: bool,
+    pub gamma_interval: Option<u32>,
+    pub metering_stride: Option<usize>,
+    pub title_format: Option<String>,
+    pub auto_gain: bool,
+    pub position: Option<Position>,
+    pub display: Option<i32>,
+    pub still_snapshot: bool,
+    pub still_threshold: Option<f64>,
+    pub still_time: Option<f64>,
+    pub adaptive_fps: Option<f64>,
+    pub adaptive_hysteresis: Option<f64>,
+    pub scale_mode: Option<ScaleMode>,
+    pub controls: bool,
+    pub software_render: bool,
+    pub scale_quality: Option<ScaleQuality>,
+    pub quality: Option<f64>,
+    pub scan_window: Option<u32>,
+    pub silent: bool,
+    pub idle_timeout: Option<f64>
+}
+
+impl Args
+{
+    pub fn parse() -> Self
+    {
+        let mut this = Self::default();
+
+        let mut args = env::args().skip(1);
+        while let Some(arg) = args.next()
+        {
+            match arg.as_str()
+            {
+                "--camera" => this.camera = Some(value_of(&arg, args.next())),
+                "--resolution" => this.resolution = Some(value_of(&arg, args.next())),
+                "--fps" => this.fps = Some(value_of(&arg, args.next())),
+                "--crosshair-color" => this.crosshair_color = Some(value_of(&arg, args.next())),
+                "--target-brightness" => this.target_brightness = Some(value_of(&arg, args.next())),
+                "--brightness-range" => this.brightness_range = Some(value_of(&arg, args.next())),
+                "--brightness-hysteresis" => this.brightness_hysteresis = Some(value_of(&arg, args.next())),
+                "--fps-window" => this.fps_window = Some(value_of(&arg, args.next())),
+                "--max-fps" => this.max_fps = Some(value_of(&arg, args.next())),
+                "--borderless" => this.borderless = true,
+                "--motion-threshold" => this.motion_threshold = Some(value_of(&arg, args.next())),
+                "--motion-snapshot" => this.motion_snapshot = true,
+                "--timelapse" => this.timelapse = Some(value_of(&arg, args.next())),
+                "--verbose" => this.verbose = true,
+                "--burst-frames" => this.burst_frames = Some(value_of(&arg, args.next())),
+                "--max-record-frames" => this.max_record_frames = Some(value_of(&arg, args.next())),
+                "--yuyv-decode" => this.yuyv_decode = true,
+                "--vignette-strength" => this.vignette_strength = Some(value_of(&arg, args.next())),
+                "--k1" => this.k1 = Some(value_of(&arg, args.next())),
+                "--k2" => this.k2 = Some(value_of(&arg, args.next())),
+                "--list-cameras" => this.list_cameras = true,
+                "--stats-log" => this.stats_log = Some(value_of(&arg, args.next())),
+                "--cameras" => this.cameras = Some(value_of(&arg, args.next())),
+                "--window-of" => this.window_of = Some(value_of(&arg, args.next())),
+                "--mirror" => this.mirror = Some(true),
+                "--no-mirror" => this.mirror = Some(false),
+                "--flip-vertical" => this.flip_vertical = Some(true),
+                "--no-flip-vertical" => this.flip_vertical = Some(false),
+                "--posterize-levels" => this.posterize_levels = Some(value_of(&arg, args.next())),
+                "--serve" => this.serve = Some(value_of(&arg, args.next())),
+                "--replay" => this.replay = Some(value_of(&arg, args.next())),
+                "--zebra-threshold" => this.zebra_threshold = Some(value_of(&arg, args.next())),
+                "--no-resize" => this.no_resize = true,
+                "--gamma-interval" => this.gamma_interval = Some(value_of(&arg, args.next())),
+                "--metering-stride" => this.metering_stride = Some(value_of(&arg, args.next())),
+                "--title-format" => this.title_format = Some(value_of(&arg, args.next())),
+                "--auto-gain" => this.auto_gain = true,
+                "--position" => this.position = Some(value_of(&arg, args.next())),
+                "--display" => this.display = Some(value_of(&arg, args.next())),
+                "--still-snapshot" => this.still_snapshot = true,
+                "--still-threshold" => this.still_threshold = Some(value_of(&arg, args.next())),
+                "--still-time" => this.still_time = Some(value_of(&arg, args.next())),
+                "--adaptive-fps" => this.adaptive_fps = Some(value_of(&arg, args.next())),
+                "--adaptive-hysteresis" => this.adaptive_hysteresis = Some(value_of(&arg, args.next())),
+                "--scale-mode" => this.scale_mode = Some(value_of(&arg, args.next())),
+                "--controls" => this.controls = true,
+                "--software-render" => this.software_render = true,
+                "--scale-quality" => this.scale_quality = Some(value_of(&arg, args.next())),
+                "--quality" => this.quality = Some(value_of(&arg, args.next())),
+                "--scan-window" => this.scan_window = Some(value_of(&arg, args.next())),
+                "--silent" => this.silent = true,
+                "--idle-timeout" => this.idle_timeout = Some(value_of(&arg, args.next())),
+                x => fail(&format!("unknown argument: {x}"))
+            }
+        }
+
+        this
+    }
+}
+
+// every argument except the camera selection and the ones each child gets its own value of
+pub fn forwarded() -> Vec<String>
+{
+    let mut forwarded = Vec::new();
+
+    let per_window = ["--camera", "--cameras", "--window-of", "--serve", "--stats-log", "--position"];
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next()
+    {
+        if per_window.contains(&arg.as_str())
+        {
+            args.next();
+        } else
+        {
+            forwarded.push(arg);
+        }
+    }
+
+    forwarded
+}
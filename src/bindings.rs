@@ -0,0 +1,307 @@
+use std::collections::{BTreeMap, HashMap};
+
+use serde::{
+    Deserialize,
+    de::{self, IntoDeserializer}
+};
+
+use sdl2::keyboard::Keycode;
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action
+{
+    Help,
+    StatusBar,
+    Quit,
+    ResetWindow,
+    Fullscreen,
+    ScaleMode,
+    AlwaysOnTop,
+    Mirror,
+    FlipVertical,
+    Rotate,
+    NextCamera,
+    NextResolution,
+    ResetDropped,
+    FrameCounter,
+    ResetCounter,
+    Snapshot,
+    CopySnapshot,
+    Burst,
+    Record,
+    Replay,
+    Pause,
+    Histogram,
+    RgbHistogram,
+    Waveform,
+    FalseColor,
+    Zebra,
+    ClearRegion,
+    Crosshair,
+    Grid,
+    Timestamp,
+    TimestampCorner,
+    Grayscale,
+    Invert,
+    Sepia,
+    Sharpen,
+    Blur,
+    Edges,
+    Scan,
+    SoftwareWhiteBalance,
+    AutoLevels,
+    Vignette,
+    Compare,
+    EnterValue,
+    IsolateChannel,
+    Posterize,
+    Mosaic,
+    MosaicSize,
+    CenterWeighted,
+    GridMetering,
+    ToggleGammaMode,
+    ExposureLock,
+    Fullbright,
+    NightMode,
+    SoftwareGamma,
+    Calibrate,
+    GammaUp,
+    GammaDown,
+    ContrastUp,
+    ContrastDown,
+    SaturationUp,
+    SaturationDown,
+    ExposureUp,
+    ExposureDown,
+    WhiteBalanceUp,
+    WhiteBalanceDown,
+    FocusIn,
+    FocusOut,
+    AutoFocus,
+    ZoomIn,
+    ZoomOut,
+    PanTilt,
+    ResetGamma,
+    ResetBrightness,
+    ResetContrast,
+    ResetSaturation,
+    ResetExposure,
+    ResetWhiteBalance,
+    ResetGain,
+    ResetFocus,
+    ResetZoom,
+    ResetPanTilt
+}
+
+impl Action
+{
+    pub fn from_name(name: &str) -> Option<Self>
+    {
+        let deserializer: de::value::StrDeserializer<de::value::Error> = name.into_deserializer();
+
+        Self::deserialize(deserializer).ok()
+    }
+
+    pub fn description(&self) -> &'static str
+    {
+        match self
+        {
+            Self::Help => "toggle this help",
+            Self::StatusBar => "status bar",
+            Self::Quit => "exit",
+            Self::ResetWindow => "reset window size and crop",
+            Self::AlwaysOnTop => "keep the window on top",
+            Self::Fullscreen => "toggle fullscreen",
+            Self::ScaleMode => "cycle fit, fill and stretch",
+            Self::Mirror => "mirror horizontally",
+            Self::FlipVertical => "flip vertically",
+            Self::Rotate => "rotate by 90 degrees",
+            Self::NextCamera => "switch to the next camera",
+            Self::NextResolution => "switch to the next resolution",
+            Self::ResetDropped => "reset the dropped frame count",
+            Self::FrameCounter => "show frames and elapsed time",
+            Self::ResetCounter => "reset the frame counter",
+            Self::Snapshot => "save a snapshot",
+            Self::CopySnapshot => "copy a snapshot to the clipboard",
+            Self::Burst => "save a burst of snapshots",
+            Self::Record => "start or stop recording",
+            Self::Replay => "save the last few seconds",
+            Self::Pause => "freeze the frame",
+            Self::Histogram => "luminance histogram",
+            Self::RgbHistogram => "per channel histogram",
+            Self::Waveform => "luminance waveform",
+            Self::FalseColor => "false color exposure map",
+            Self::Zebra => "zebra stripes on highlights",
+            Self::ClearRegion => "clear the selected region",
+            Self::Crosshair => "center crosshair",
+            Self::Grid => "rule of thirds grid",
+            Self::Timestamp => "timestamp burn in",
+            Self::TimestampCorner => "move the timestamp",
+            Self::Grayscale => "grayscale filter",
+            Self::Invert => "invert colors",
+            Self::Sepia => "sepia tone",
+            Self::Sharpen => "sharpen filter",
+            Self::Blur => "denoise blur",
+            Self::Edges => "edge detection view",
+            Self::Scan => "document scan view",
+            Self::SoftwareWhiteBalance => "software auto white balance",
+            Self::AutoLevels => "stretch levels automatically",
+            Self::Vignette => "vignette correction",
+            Self::Compare => "compare with a frozen reference",
+            Self::EnterValue => "type a value for the last used control",
+            Self::IsolateChannel => "show a single color channel",
+            Self::Posterize => "posterize",
+            Self::Mosaic => "mosaic, middle drag picks the area",
+            Self::MosaicSize => "change the mosaic block size",
+            Self::CenterWeighted => "center weighted metering",
+            Self::GridMetering => "balance the darkest and brightest 4x4 zones",
+            Self::ToggleGammaMode => "toggle auto gamma",
+            Self::ExposureLock => "hold the auto exposure",
+            Self::Fullbright => "fullbright",
+            Self::NightMode => "night mode",
+            Self::SoftwareGamma => "software gamma",
+            Self::Calibrate => "sweep gamma for the target brightness",
+            Self::GammaUp => "gamma up",
+            Self::GammaDown => "gamma down",
+            Self::ContrastUp => "contrast up",
+            Self::ContrastDown => "contrast down",
+            Self::SaturationUp => "saturation up",
+            Self::SaturationDown => "saturation down",
+            Self::ExposureUp => "exposure up",
+            Self::ExposureDown => "exposure down",
+            Self::WhiteBalanceUp => "white balance up",
+            Self::WhiteBalanceDown => "white balance down",
+            Self::FocusIn => "focus further",
+            Self::FocusOut => "focus closer",
+            Self::AutoFocus => "toggle autofocus",
+            Self::ZoomIn => "hardware zoom in",
+            Self::ZoomOut => "hardware zoom out",
+            Self::PanTilt => "arrows pan and tilt the camera",
+            Self::ResetGamma => "reset gamma",
+            Self::ResetBrightness => "reset brightness",
+            Self::ResetContrast => "reset contrast",
+            Self::ResetSaturation => "reset saturation",
+            Self::ResetExposure => "reset exposure",
+            Self::ResetWhiteBalance => "reset white balance",
+            Self::ResetGain => "reset gain",
+            Self::ResetFocus => "reset focus and autofocus",
+            Self::ResetZoom => "reset hardware zoom",
+            Self::ResetPanTilt => "reset pan and tilt"
+        }
+    }
+}
+
+pub fn default_bindings() -> Vec<(Keycode, Action)>
+{
+    vec![
+        (Keycode::F1, Action::Help),
+        (Keycode::Kp2, Action::StatusBar),
+        (Keycode::Space, Action::ResetWindow),
+        (Keycode::Escape, Action::Quit),
+        (Keycode::Q, Action::Quit),
+        (Keycode::F11, Action::Fullscreen),
+        (Keycode::Num0, Action::ScaleMode),
+        (Keycode::T, Action::AlwaysOnTop),
+        (Keycode::M, Action::Mirror),
+        (Keycode::V, Action::FlipVertical),
+        (Keycode::R, Action::Rotate),
+        (Keycode::Tab, Action::NextCamera),
+        (Keycode::F8, Action::NextResolution),
+        (Keycode::Backspace, Action::ResetDropped),
+        (Keycode::Kp1, Action::FrameCounter),
+        (Keycode::Kp0, Action::ResetCounter),
+        (Keycode::S, Action::Snapshot),
+        (Keycode::Backquote, Action::CopySnapshot),
+        (Keycode::D, Action::Burst),
+        (Keycode::C, Action::Record),
+        (Keycode::F10, Action::Replay),
+        (Keycode::P, Action::Pause),
+        (Keycode::H, Action::Histogram),
+        (Keycode::KpDivide, Action::RgbHistogram),
+        (Keycode::F9, Action::Waveform),
+        (Keycode::F12, Action::FalseColor),
+        (Keycode::Insert, Action::Zebra),
+        (Keycode::Delete, Action::ClearRegion),
+        (Keycode::X, Action::Crosshair),
+        (Keycode::F2, Action::Grid),
+        (Keycode::F3, Action::Timestamp),
+        (Keycode::F4, Action::TimestampCorner),
+        (Keycode::Y, Action::Grayscale),
+        (Keycode::I, Action::Invert),
+        (Keycode::E, Action::Sepia),
+        (Keycode::U, Action::Sharpen),
+        (Keycode::B, Action::Blur),
+        (Keycode::O, Action::Edges),
+        (Keycode::Kp4, Action::Scan),
+        (Keycode::A, Action::SoftwareWhiteBalance),
+        (Keycode::N, Action::AutoLevels),
+        (Keycode::Z, Action::Vignette),
+        (Keycode::Backslash, Action::Compare),
+        (Keycode::Slash, Action::EnterValue),
+        (Keycode::F5, Action::IsolateChannel),
+        (Keycode::F6, Action::Posterize),
+        (Keycode::Num8, Action::Mosaic),
+        (Keycode::Num9, Action::MosaicSize),
+        (Keycode::W, Action::CenterWeighted),
+        (Keycode::F7, Action::GridMetering),
+        (Keycode::G, Action::ToggleGammaMode),
+        (Keycode::Kp3, Action::ExposureLock),
+        (Keycode::F, Action::Fullbright),
+        (Keycode::Num7, Action::NightMode),
+        (Keycode::J, Action::SoftwareGamma),
+        (Keycode::KpMultiply, Action::Calibrate),
+        (Keycode::Up, Action::GammaUp),
+        (Keycode::Down, Action::GammaDown),
+        (Keycode::RightBracket, Action::ContrastUp),
+        (Keycode::LeftBracket, Action::ContrastDown),
+        (Keycode::Period, Action::SaturationUp),
+        (Keycode::Comma, Action::SaturationDown),
+        (Keycode::Quote, Action::ExposureUp),
+        (Keycode::Semicolon, Action::ExposureDown),
+        (Keycode::L, Action::WhiteBalanceUp),
+        (Keycode::K, Action::WhiteBalanceDown),
+        (Keycode::PageUp, Action::FocusIn),
+        (Keycode::PageDown, Action::FocusOut),
+        (Keycode::Home, Action::AutoFocus),
+        (Keycode::KpPlus, Action::ZoomIn),
+        (Keycode::KpMinus, Action::ZoomOut),
+        (Keycode::End, Action::PanTilt),
+        (Keycode::Num1, Action::ResetGamma),
+        (Keycode::Num2, Action::ResetBrightness),
+        (Keycode::Num3, Action::ResetContrast),
+        (Keycode::Num4, Action::ResetSaturation),
+        (Keycode::Num5, Action::ResetExposure),
+        (Keycode::Num6, Action::ResetWhiteBalance),
+        (Keycode::Kp5, Action::ResetGain),
+        (Keycode::Kp6, Action::ResetFocus),
+        (Keycode::Kp7, Action::ResetZoom),
+        (Keycode::Kp8, Action::ResetPanTilt)
+    ]
+}
+
+pub fn load_bindings(overrides: &BTreeMap<String, String>) -> HashMap<Keycode, Action>
+{
+    let mut bindings: HashMap<Keycode, Action> = default_bindings().into_iter().collect();
+
+    overrides.iter().for_each(|(name, key)|
+    {
+        let Some(action) = Action::from_name(name) else
+        {
+            eprintln!("unknown action in keybindings: {name}");
+            return;
+        };
+
+        let Some(keycode) = Keycode::from_name(key) else
+        {
+            eprintln!("unknown key for {name}: {key}");
+            return;
+        };
+
+        bindings.retain(|_, bound| *bound != action);
+        bindings.insert(keycode, action);
+    });
+
+    bindings
+}
@@ -0,0 +1,119 @@
+use std::{
+    env,
+    fs,
+    path::PathBuf,
+    time::SystemTime,
+    collections::BTreeMap
+};
+
+use serde::{Serialize, Deserialize};
+
+
+// filters that start enabled, under [filters]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FilterDefaults
+{
+    pub grayscale: bool,
+    pub invert: bool,
+    pub sepia: bool,
+    pub sharpen: bool,
+    pub blur: bool,
+    pub edges: bool,
+    pub white_balance: bool,
+    pub auto_levels: bool,
+    pub vignette: bool,
+    pub posterize: bool,
+    pub scan: bool
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config
+{
+    pub auto_gamma: bool,
+    pub gamma: Option<i64>,
+    pub mirrored: bool,
+    pub flipped_vertical: bool,
+    pub fps: Option<u32>,
+    pub always_on_top: Option<bool>,
+    pub target_brightness: Option<f64>,
+    pub window_position: Option<(i32, i32)>,
+    pub filters: FilterDefaults,
+    pub keybindings: BTreeMap<String, String>
+}
+
+impl Config
+{
+    // each window of a multi camera session keeps its own file
+    pub fn path(camera: Option<u32>) -> Option<PathBuf>
+    {
+        let config_dir = env::var_os("XDG_CONFIG_HOME").map(PathBuf::from)
+            .or_else(|| env::var_os("APPDATA").map(PathBuf::from))
+            .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+
+        let name = camera.map(|index| format!("config-camera{index}.toml"))
+            .unwrap_or_else(|| "config.toml".to_owned());
+
+        Some(config_dir.join("cameradisplay").join(name))
+    }
+
+    pub fn modified(camera: Option<u32>) -> Option<SystemTime>
+    {
+        fs::metadata(Self::path(camera)?).and_then(|metadata| metadata.modified()).ok()
+    }
+
+    // a window without its own file yet starts from the shared one
+    pub fn load(camera: Option<u32>) -> Self
+    {
+        Self::reload(camera)
+            .or_else(|| camera.and_then(|_| Self::reload(None)))
+            .unwrap_or_default()
+    }
+
+    // none if the config is missing or invalid
+    pub fn reload(camera: Option<u32>) -> Option<Self>
+    {
+        let path = Self::path(camera)?;
+
+        let text = fs::read_to_string(&path).ok()?;
+
+        toml::from_str(&text).inspect_err(|err|
+        {
+            eprintln!("error parsing config at {}: {err}", path.display());
+        }).ok()
+    }
+
+    pub fn save(&self, camera: Option<u32>)
+    {
+        let Some(path) = Self::path(camera) else
+        {
+            eprintln!("couldnt find a config directory");
+            return;
+        };
+
+        let text = match toml::to_string(self)
+        {
+            Ok(x) => x,
+            Err(err) =>
+            {
+                eprintln!("error serializing config: {err}");
+                return;
+            }
+        };
+
+        if let Some(parent) = path.parent()
+        {
+            if let Err(err) = fs::create_dir_all(parent)
+            {
+                eprintln!("error creating config directory: {err}");
+                return;
+            }
+        }
+
+        if let Err(err) = fs::write(&path, text)
+        {
+            eprintln!("error saving config to {}: {err}", path.display());
+        }
+    }
+}
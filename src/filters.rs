@@ -0,0 +1,654 @@
+use image::{Rgb, RgbImage};
+
+use crate::{font, lightness, pixel_luminance, LUMINANCE_WEIGHTS};
+
+
+pub struct GammaLut
+{
+    gamma: f64,
+    table: [u8; 256]
+}
+
+impl GammaLut
+{
+    pub fn new(gamma: f64) -> Self
+    {
+        let table = std::array::from_fn(|index|
+        {
+            let value = index as f64 / u8::MAX as f64;
+
+            (value.powf(gamma.recip()) * u8::MAX as f64).round() as u8
+        });
+
+        Self{gamma, table}
+    }
+
+    pub fn gamma(&self) -> f64
+    {
+        self.gamma
+    }
+
+    pub fn apply(&self, image: &mut RgbImage)
+    {
+        image.iter_mut().for_each(|value| *value = self.table[*value as usize]);
+    }
+}
+
+pub struct VignetteMap
+{
+    size: (u32, u32),
+    gains: Vec<f32>
+}
+
+impl VignetteMap
+{
+    pub fn new(width: u32, height: u32, strength: f64) -> Self
+    {
+        let half_width = width as f64 * 0.5;
+        let half_height = height as f64 * 0.5;
+
+        let max_distance = (half_width * half_width + half_height * half_height).max(1.0);
+
+        let gains = (0..height).flat_map(|y|
+        {
+            (0..width).map(move |x|
+            {
+                let dx = x as f64 + 0.5 - half_width;
+                let dy = y as f64 + 0.5 - half_height;
+
+                let distance = (dx * dx + dy * dy) / max_distance;
+
+                (1.0 + strength * distance) as f32
+            })
+        }).collect();
+
+        Self{size: (width, height), gains}
+    }
+
+    pub fn size(&self) -> (u32, u32)
+    {
+        self.size
+    }
+
+    pub fn apply(&self, image: &mut RgbImage)
+    {
+        image.pixels_mut().zip(self.gains.iter()).for_each(|(Rgb(pixel), &gain)|
+        {
+            pixel.iter_mut().for_each(|value|
+            {
+                *value = (*value as f32 * gain).round().min(u8::MAX as f32) as u8;
+            });
+        });
+    }
+}
+
+pub struct UndistortMap
+{
+    size: (u32, u32),
+    sources: Vec<Option<(f32, f32)>>
+}
+
+impl UndistortMap
+{
+    pub fn new(width: u32, height: u32, k1: f64, k2: f64) -> Self
+    {
+        let half_width = width as f64 * 0.5;
+        let half_height = height as f64 * 0.5;
+
+        let normalizer = half_width.hypot(half_height).max(1.0);
+
+        let sources = (0..height).flat_map(|y|
+        {
+            (0..width).map(move |x|
+            {
+                let dx = (x as f64 + 0.5 - half_width) / normalizer;
+                let dy = (y as f64 + 0.5 - half_height) / normalizer;
+
+                let r2 = dx * dx + dy * dy;
+                let scale = 1.0 + k1 * r2 + k2 * r2 * r2;
+
+                let source_x = dx * scale * normalizer + half_width - 0.5;
+                let source_y = dy * scale * normalizer + half_height - 0.5;
+
+                let inside = (0.0..=(width - 1) as f64).contains(&source_x)
+                    && (0.0..=(height - 1) as f64).contains(&source_y);
+
+                inside.then_some((source_x as f32, source_y as f32))
+            })
+        }).collect();
+
+        Self{size: (width, height), sources}
+    }
+
+    pub fn size(&self) -> (u32, u32)
+    {
+        self.size
+    }
+
+    pub fn apply(&self, image: &RgbImage) -> RgbImage
+    {
+        let (width, height) = self.size;
+
+        let sample = |x: u32, y: u32| -> [f32; 3]
+        {
+            image.get_pixel(x.min(width - 1), y.min(height - 1)).0.map(|value| value as f32)
+        };
+
+        let data = self.sources.iter().flat_map(|source|
+        {
+            let Some((x, y)) = *source else
+            {
+                return [0; 3];
+            };
+
+            let (left, top) = (x.floor() as u32, y.floor() as u32);
+            let (fraction_x, fraction_y) = (x.fract(), y.fract());
+
+            let lerp = |a: [f32; 3], b: [f32; 3], t: f32| -> [f32; 3]
+            {
+                std::array::from_fn(|i| a[i] + (b[i] - a[i]) * t)
+            };
+
+            let upper = lerp(sample(left, top), sample(left + 1, top), fraction_x);
+            let lower = lerp(sample(left, top + 1), sample(left + 1, top + 1), fraction_x);
+
+            lerp(upper, lower, fraction_y).map(|value| value.round() as u8)
+        }).collect();
+
+        RgbImage::from_raw(width, height, data).unwrap()
+    }
+}
+
+pub fn split_compare(image: &mut RgbImage, reference: &RgbImage)
+{
+    let (width, height) = image.dimensions();
+    let divider = width / 2;
+
+    for y in 0..height
+    {
+        for x in 0..divider
+        {
+            image.put_pixel(x, y, *reference.get_pixel(x, y));
+        }
+
+        for x in divider.saturating_sub(1)..(divider + 1).min(width)
+        {
+            image.put_pixel(x, y, Rgb([255, 255, 255]));
+        }
+    }
+}
+
+pub fn stretch_levels(image: &mut RgbImage, low: u8, high: u8)
+{
+    if high <= low
+    {
+        return;
+    }
+
+    let range = (high - low) as f64;
+
+    let table: [u8; 256] = std::array::from_fn(|index|
+    {
+        let value = (index as f64 - low as f64) / range;
+
+        (value.clamp(0.0, 1.0) * u8::MAX as f64).round() as u8
+    });
+
+    image.iter_mut().for_each(|value| *value = table[*value as usize]);
+}
+
+pub fn posterize(image: &mut RgbImage, levels: u32)
+{
+    let steps = levels.clamp(2, 256) - 1;
+
+    image.iter_mut().for_each(|value|
+    {
+        let level = (*value as u32 * steps + 127) / 255;
+
+        *value = (level * 255 / steps) as u8;
+    });
+}
+
+pub fn mosaic(image: &mut RgbImage, block: u32, (x, y, width, height): (u32, u32, u32, u32))
+{
+    let block = block.max(1);
+
+    let right = (x + width).min(image.width());
+    let bottom = (y + height).min(image.height());
+
+    (y..bottom).step_by(block as usize).for_each(|block_y|
+    {
+        (x..right).step_by(block as usize).for_each(|block_x|
+        {
+            let block_right = (block_x + block).min(right);
+            let block_bottom = (block_y + block).min(bottom);
+
+            let total = (block_right - block_x) * (block_bottom - block_y);
+
+            let mut sums = [0_u32; 3];
+            (block_y..block_bottom).for_each(|py|
+            {
+                (block_x..block_right).for_each(|px|
+                {
+                    let Rgb(pixel) = image.get_pixel(px, py);
+
+                    sums.iter_mut().zip(pixel).for_each(|(sum, value)| *sum += *value as u32);
+                });
+            });
+
+            let average = Rgb(sums.map(|sum| (sum / total) as u8));
+
+            (block_y..block_bottom).for_each(|py|
+            {
+                (block_x..block_right).for_each(|px| image.put_pixel(px, py, average));
+            });
+        });
+    });
+}
+
+pub fn false_color(image: &mut RgbImage)
+{
+    image.pixels_mut().for_each(|pixel|
+    {
+        let lightness = lightness(pixel_luminance(pixel));
+
+        *pixel = if lightness < 5.0
+        {
+            Rgb([0, 0, 255])
+        } else if lightness > 95.0
+        {
+            Rgb([255, 0, 0])
+        } else if (45.0..=55.0).contains(&lightness)
+        {
+            Rgb([0, 200, 0])
+        } else
+        {
+            let gray = (lightness / 100.0 * u8::MAX as f64).round() as u8;
+
+            Rgb([gray; 3])
+        };
+    });
+}
+
+pub fn zebra(image: &mut RgbImage, threshold: f64, phase: u32)
+{
+    let period = 8;
+
+    image.enumerate_pixels_mut().for_each(|(x, y, pixel)|
+    {
+        let striped = (x + y + phase % period) % period < period / 2;
+
+        if striped && lightness(pixel_luminance(pixel)) > threshold
+        {
+            *pixel = Rgb([0, 0, 0]);
+        }
+    });
+}
+
+pub fn channel_means(image: &RgbImage) -> [f64; 3]
+{
+    let mut sums = [0_u64; 3];
+
+    image.pixels().for_each(|Rgb(pixel)|
+    {
+        sums.iter_mut().zip(pixel).for_each(|(sum, &value)| *sum += value as u64);
+    });
+
+    let total = (image.width() as u64 * image.height() as u64).max(1) as f64;
+
+    sums.map(|sum| sum as f64 / total)
+}
+
+pub fn white_balance(image: &mut RgbImage, means: [f64; 3])
+{
+    let gray = means.iter().sum::<f64>() / 3.0;
+
+    let gains = means.map(|mean|
+    {
+        if mean > 0.0
+        {
+            gray / mean
+        } else
+        {
+            1.0
+        }
+    });
+
+    image.pixels_mut().for_each(|Rgb(pixel)|
+    {
+        pixel.iter_mut().zip(gains).for_each(|(value, gain)|
+        {
+            *value = (*value as f64 * gain).round().min(u8::MAX as f64) as u8;
+        });
+    });
+}
+
+fn convolve3x3(image: &RgbImage, kernel: [[i32; 3]; 3]) -> RgbImage
+{
+    let (width, height) = image.dimensions();
+    let (width, height) = (width as usize, height as usize);
+
+    let source = image.as_raw();
+    let mut output = vec![0; source.len()];
+
+    let clamped = |value: usize, offset: usize, limit: usize| -> usize
+    {
+        (value + offset).saturating_sub(1).min(limit - 1)
+    };
+
+    for y in 0..height
+    {
+        for x in 0..width
+        {
+            let mut sum = [0_i32; 3];
+
+            kernel.iter().enumerate().for_each(|(kernel_y, row)|
+            {
+                let source_y = clamped(y, kernel_y, height);
+
+                row.iter().enumerate().for_each(|(kernel_x, weight)|
+                {
+                    let source_x = clamped(x, kernel_x, width);
+                    let index = (source_y * width + source_x) * 3;
+
+                    sum.iter_mut().zip(&source[index..index + 3]).for_each(|(sum, &value)|
+                    {
+                        *sum += value as i32 * weight;
+                    });
+                });
+            });
+
+            let index = (y * width + x) * 3;
+            output[index..index + 3].iter_mut().zip(sum).for_each(|(output, sum)|
+            {
+                *output = sum.clamp(0, u8::MAX as i32) as u8;
+            });
+        }
+    }
+
+    RgbImage::from_raw(width as u32, height as u32, output).unwrap()
+}
+
+pub fn sharpen(image: &RgbImage) -> RgbImage
+{
+    convolve3x3(image, [
+        [0, -1, 0],
+        [-1, 5, -1],
+        [0, -1, 0]
+    ])
+}
+
+fn box_pass(source: &[u8], output: &mut [u8], (width, height): (usize, usize), horizontal: bool)
+{
+    for y in 0..height
+    {
+        for x in 0..width
+        {
+            let (position, limit) = if horizontal
+            {
+                (x, width)
+            } else
+            {
+                (y, height)
+            };
+
+            let low = position.saturating_sub(1);
+            let high = (position + 1).min(limit - 1);
+
+            let index_of = |position: usize|
+            {
+                if horizontal
+                {
+                    (y * width + position) * 3
+                } else
+                {
+                    (position * width + x) * 3
+                }
+            };
+
+            let index = index_of(position);
+            (0..3).for_each(|channel|
+            {
+                let sum: u32 = [low, position, high].into_iter().map(|position|
+                {
+                    source[index_of(position) + channel] as u32
+                }).sum();
+
+                output[index + channel] = (sum / 3) as u8;
+            });
+        }
+    }
+}
+
+pub fn box_blur(image: &mut RgbImage)
+{
+    let (width, height) = image.dimensions();
+    let size = (width as usize, height as usize);
+
+    let mut horizontal = vec![0; image.as_raw().len()];
+    box_pass(image.as_raw(), &mut horizontal, size, true);
+
+    box_pass(&horizontal, image, size, false);
+}
+
+pub fn sepia(image: &mut RgbImage)
+{
+    image.pixels_mut().for_each(|Rgb(pixel)|
+    {
+        let [r, g, b] = pixel.map(|x| x as f32);
+
+        let tone = |r_weight: f32, g_weight: f32, b_weight: f32|
+        {
+            (r * r_weight + g * g_weight + b * b_weight).round().min(u8::MAX as f32) as u8
+        };
+
+        *pixel = [
+            tone(0.393, 0.769, 0.189),
+            tone(0.349, 0.686, 0.168),
+            tone(0.272, 0.534, 0.131)
+        ];
+    });
+}
+
+pub fn sobel(image: &RgbImage) -> RgbImage
+{
+    let (width, height) = image.dimensions();
+    let (width, height) = (width as usize, height as usize);
+
+    let luminance: Vec<f64> = image.pixels().map(|Rgb(pixel)|
+    {
+        pixel.iter().zip(LUMINANCE_WEIGHTS).map(|(&value, weight)| value as f64 * weight).sum()
+    }).collect();
+
+    let at = |x: usize, y: usize, offset_x: usize, offset_y: usize|
+    {
+        let x = (x + offset_x).saturating_sub(1).min(width - 1);
+        let y = (y + offset_y).saturating_sub(1).min(height - 1);
+
+        luminance[y * width + x]
+    };
+
+    let mut output = Vec::with_capacity(width * height * 3);
+    for y in 0..height
+    {
+        for x in 0..width
+        {
+            let horizontal = (at(x, y, 2, 0) + at(x, y, 2, 1) * 2.0 + at(x, y, 2, 2))
+                - (at(x, y, 0, 0) + at(x, y, 0, 1) * 2.0 + at(x, y, 0, 2));
+
+            let vertical = (at(x, y, 0, 2) + at(x, y, 1, 2) * 2.0 + at(x, y, 2, 2))
+                - (at(x, y, 0, 0) + at(x, y, 1, 0) * 2.0 + at(x, y, 2, 0));
+
+            let value = horizontal.hypot(vertical).min(u8::MAX as f64) as u8;
+
+            output.extend([value; 3]);
+        }
+    }
+
+    RgbImage::from_raw(width as u32, height as u32, output).unwrap()
+}
+
+// black text on white, thresholded against the mean of a window around each pixel
+pub fn adaptive_threshold(image: &RgbImage, window: u32) -> RgbImage
+{
+    let (width, height) = image.dimensions();
+    let (width, height) = (width as usize, height as usize);
+
+    let luminance: Vec<f64> = image.pixels().map(|Rgb(pixel)|
+    {
+        pixel.iter().zip(LUMINANCE_WEIGHTS).map(|(&value, weight)| value as f64 * weight).sum()
+    }).collect();
+
+    let stride = width + 1;
+    let mut integral = vec![0.0; stride * (height + 1)];
+    for y in 0..height
+    {
+        let mut row = 0.0;
+        for x in 0..width
+        {
+            row += luminance[y * width + x];
+            integral[(y + 1) * stride + x + 1] = integral[y * stride + x + 1] + row;
+        }
+    }
+
+    let half = (window / 2).max(1) as usize;
+    let offset = 10.0;
+
+    let mut output = Vec::with_capacity(width * height * 3);
+    for y in 0..height
+    {
+        let (top, bottom) = (y.saturating_sub(half), (y + half + 1).min(height));
+
+        for x in 0..width
+        {
+            let (left, right) = (x.saturating_sub(half), (x + half + 1).min(width));
+
+            let sum = integral[bottom * stride + right] - integral[top * stride + right]
+                - integral[bottom * stride + left] + integral[top * stride + left];
+
+            let mean = sum / ((right - left) * (bottom - top)) as f64;
+
+            let value = if luminance[y * width + x] < mean - offset { 0 } else { u8::MAX };
+
+            output.extend([value; 3]);
+        }
+    }
+
+    RgbImage::from_raw(width as u32, height as u32, output).unwrap()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Corner
+{
+    TopLeft,
+    TopRight,
+    BottomRight,
+    BottomLeft
+}
+
+impl Corner
+{
+    pub fn next(self) -> Self
+    {
+        match self
+        {
+            Self::TopLeft => Self::TopRight,
+            Self::TopRight => Self::BottomRight,
+            Self::BottomRight => Self::BottomLeft,
+            Self::BottomLeft => Self::TopLeft
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Channel
+{
+    All,
+    Red,
+    Green,
+    Blue
+}
+
+impl Channel
+{
+    pub fn next(self) -> Self
+    {
+        match self
+        {
+            Self::All => Self::Red,
+            Self::Red => Self::Green,
+            Self::Green => Self::Blue,
+            Self::Blue => Self::All
+        }
+    }
+
+    pub fn index(self) -> Option<usize>
+    {
+        match self
+        {
+            Self::All => None,
+            Self::Red => Some(0),
+            Self::Green => Some(1),
+            Self::Blue => Some(2)
+        }
+    }
+}
+
+pub fn isolate_channel(image: &mut RgbImage, index: usize)
+{
+    image.pixels_mut().for_each(|Rgb(pixel)|
+    {
+        pixel.iter_mut().enumerate().filter(|(i, _)| *i != index).for_each(|(_, value)| *value = 0);
+    });
+}
+
+pub fn burn_in_text(image: &mut RgbImage, text: &str, corner: Corner)
+{
+    let (width, height) = image.dimensions();
+
+    let scale = (height / 240).max(1);
+    let padding = 2 * scale;
+
+    let (text_width, text_height) = font::text_size(text);
+    let (text_width, text_height) = (text_width * scale, text_height * scale);
+
+    let strip_width = (text_width + padding * 2).min(width);
+    let strip_height = (text_height + padding * 2).min(height);
+
+    let strip_x = match corner
+    {
+        Corner::TopLeft | Corner::BottomLeft => 0,
+        Corner::TopRight | Corner::BottomRight => width - strip_width
+    };
+
+    let strip_y = match corner
+    {
+        Corner::TopLeft | Corner::TopRight => 0,
+        Corner::BottomLeft | Corner::BottomRight => height - strip_height
+    };
+
+    for y in strip_y..strip_y + strip_height
+    {
+        for x in strip_x..strip_x + strip_width
+        {
+            image.get_pixel_mut(x, y).0.iter_mut().for_each(|value| *value /= 4);
+        }
+    }
+
+    font::for_each_pixel(text, |pixel_x, pixel_y|
+    {
+        for offset_y in 0..scale
+        {
+            for offset_x in 0..scale
+            {
+                let x = strip_x + padding + pixel_x * scale + offset_x;
+                let y = strip_y + padding + pixel_y * scale + offset_y;
+
+                if x < width && y < height
+                {
+                    image.put_pixel(x, y, Rgb([255, 255, 255]));
+                }
+            }
+        }
+    });
+}
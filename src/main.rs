@@ -1,45 +1,114 @@
 use std::{
+    env,
+    mem,
     thread,
-    time::Duration,
+    collections::VecDeque,
+    cell::OnceCell,
+    ops::{Range, RangeInclusive, Deref, DerefMut},
+    path::Path,
+    process::{self, Command, Stdio},
+    fs::{self, File, OpenOptions},
+    io::{Write, BufWriter, Cursor},
+    time::{Duration, SystemTime, UNIX_EPOCH},
     sync::{
         Arc,
         Mutex,
-        mpsc::{self, TryRecvError}
+        MutexGuard,
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        mpsc::{self, TryRecvError, TrySendError}
     },
     time::Instant
 };
 
-use image::{Rgb, DynamicImage, RgbImage};
+use image::{
+    Rgb,
+    Frame,
+    Delay,
+    DynamicImage,
+    RgbImage,
+    ImageResult,
+    ImageFormat,
+    ImageEncoder,
+    ExtendedColorType,
+    imageops,
+    codecs::{
+        gif::{GifEncoder, Repeat},
+        png::{PngEncoder, CompressionType, FilterType}
+    }
+};
+
+use chrono::Local;
 
 use nokhwa::{
     Camera,
+    Buffer,
+    NokhwaError,
     pixel_format::RgbFormat,
     utils::{
         RequestedFormat,
         RequestedFormatType,
         CameraIndex,
+        CameraFormat,
+        FrameFormat,
         Resolution,
-        CameraControl,
         KnownCameraControl,
         ControlValueSetter,
+        all_known_camera_controls,
         ControlValueDescription
     }
 };
 
 use sdl2::{
-    rect::Rect,
+    VideoSubsystem,
+    AudioSubsystem,
+    audio::{AudioQueue, AudioSpecDesired},
+    rect::{Rect, Point},
     keyboard::{Mod, Keycode},
-    pixels::PixelFormatEnum,
+    pixels::{Color, PixelFormatEnum},
     event::{WindowEvent, Event},
-    render::{Texture, WindowCanvas}
+    mouse::MouseButton,
+    video::{FullscreenType, WindowPos},
+    render::{BlendMode, Texture, WindowCanvas}
 };
 
+use args::{Args, Size, HexColor, CameraList, Position, ScaleMode, ScaleQuality};
+use config::Config;
+use bindings::Action;
+use serve::MjpegServer;
+use filters::{Corner, Channel, GammaLut, VignetteMap, UndistortMap};
+
+mod args;
+mod config;
+mod font;
+mod filters;
+mod bindings;
+mod serve;
+
 
 const UPDATE_FPS: u32 = 60;
+const LUMINANCE_WEIGHTS: [f64; 3] = [0.2126, 0.7152, 0.0722];
+const MAX_RECORDING_FRAMES: usize = 600;
+const RECONNECT_AFTER_ERRORS: u32 = 30;
+// V4L2_CID_FOCUS_AUTO, nokhwa has no known control for it
+const AUTO_FOCUS_CONTROL: u128 = 0x009a090c;
+// V4L2_CID_CAMERA_SENSOR_ROTATION, counter clockwise degrees
+const SENSOR_ROTATION_CONTROL: u128 = 0x009a0923;
+const WAVEFORM_COLUMNS: usize = 128;
+const WAVEFORM_ROWS: usize = 64;
+// motion is compared on every nth pixel in both directions
+const MOTION_DOWNSCALE: usize = 4;
+// how far apart windows opened together with --position start
+const WINDOW_CASCADE: i32 = 48;
 
 fn set_closest_aspect(window: &mut WindowCanvas, aspect: f64) -> bool
 {
     let window = window.window_mut();
+
+    if window.fullscreen_state() != FullscreenType::Off
+    {
+        return false;
+    }
+
     let (width, height) = window.size();
 
     let height_scaled = height as f64 * aspect;
@@ -65,692 +134,4694 @@ fn set_closest_aspect(window: &mut WindowCanvas, aspect: f64) -> bool
     true
 }
 
-struct Averager<const WINDOW_SIZE: usize>
+// none means centered
+fn startup_position(
+    video: &VideoSubsystem,
+    position: Option<(i32, i32)>,
+    display: Option<i32>,
+    (width, height): (u32, u32)
+) -> Option<(i32, i32)>
 {
-    window: [f64; WINDOW_SIZE],
-    index: usize
-}
+    let displays = video.num_video_displays().unwrap_or(0);
 
-impl<const WINDOW_SIZE: usize> Averager<WINDOW_SIZE>
-{
-    pub fn new() -> Self
+    if let Some((x, y)) = position
     {
-        Self{window: [0.0; WINDOW_SIZE], index: 0}
+        let visible = (0..displays).filter_map(|index| video.display_bounds(index).ok()).any(|bounds|
+        {
+            bounds.contains_point((x, y))
+        });
+
+        if visible
+        {
+            return Some((x, y));
+        }
+
+        eprintln!("window position {x},{y} is off screen, centering");
     }
 
-    pub fn add(&mut self, value: f64) -> f64
+    let display = display?;
+
+    match video.display_bounds(display)
     {
-        self.window[self.index] = value;
+        Ok(bounds) =>
+        {
+            let x = bounds.x() + (bounds.width() as i32 - width as i32) / 2;
+            let y = bounds.y() + (bounds.height() as i32 - height as i32) / 2;
 
-        self.index += 1;
-        if self.index == self.window.len()
+            Some((x, y))
+        },
+        Err(err) =>
         {
-            self.index = 0;
+            eprintln!("display {display} unavailable ({err}), centering");
+
+            None
         }
+    }
+}
+
+fn open_beep(audio: &AudioSubsystem) -> Result<(AudioQueue<i16>, Vec<i16>), String>
+{
+    let spec = AudioSpecDesired{freq: Some(44100), channels: Some(1), samples: None};
+
+    let queue = audio.open_queue::<i16, _>(None, &spec)?;
+
+    let freq = queue.spec().freq as f32;
+    let channels = queue.spec().channels as usize;
+
+    let length = (freq * 0.08) as usize;
+    let samples = (0..length).flat_map(|index|
+    {
+        let t = index as f32 / freq;
+        let fade = 1.0 - index as f32 / length as f32;
+
+        let value = (t * 1800.0 * std::f32::consts::TAU).sin() * fade * 0.3;
+
+        std::iter::repeat_n((value * i16::MAX as f32) as i16, channels)
+    }).collect();
 
-        self.window.iter().copied().sum::<f64>() / WINDOW_SIZE as f64
+    Ok((queue, samples))
+}
+
+fn srgb_to_linear(x: u8) -> f64
+{
+    let value = x as f64 / u8::MAX as f64;
+
+    if value < 0.04045
+    {
+        value / 12.92
+    } else
+    {
+        ((value + 0.055) / 1.055).powf(2.4)
     }
 }
 
-#[derive(Debug, Clone)]
-enum ProgramMessage
+fn linear_to_srgb(value: f64) -> u8
 {
-    Render(Box<RgbImage>),
-    ResetWindow,
-    SetClosestAspect,
-    SetTitle(String)
+    let value = if value <= 0.0031308
+    {
+        value * 12.92
+    } else
+    {
+        value.powf(2.4_f64.recip()) * 1.055 - 0.055
+    };
+
+    (value.clamp(0.0, 1.0) * u8::MAX as f64).round() as u8
 }
 
-#[allow(dead_code)]
-struct ControlInfo
+fn pixel_luminance(&Rgb(pixel): &Rgb<u8>) -> f64
 {
-    pub min: i64,
-    pub max: i64,
-    pub value: i64,
-    pub step: i64,
-    pub default: i64
+    pixel.into_iter().zip(LUMINANCE_WEIGHTS).map(|(value, weight)| srgb_to_linear(value) * weight).sum()
 }
 
-struct ControlController
+fn lightness(luminance: f64) -> f64
 {
-    control: Option<CameraControl>,
-    current: i64,
-    which: KnownCameraControl
+    if luminance <= 0.008856
+    {
+        luminance * 903.3
+    } else
+    {
+        luminance.cbrt() * 116.0 - 16.0
+    }
 }
 
-impl ControlController
+fn lightness_to_luminance(lightness: f64) -> f64
 {
-    pub fn new(camera: &Camera, which: KnownCameraControl) -> Self
+    if lightness <= 8.0
+    {
+        lightness / 903.3
+    } else
     {
-        let control = camera.camera_control(which).ok();
+        ((lightness + 16.0) / 116.0).powi(3)
+    }
+}
 
-        let mut this = Self{control, current: 0, which};
+// mean luminance of one in every stride pixels of the region, counted in reading order
+fn region_luminance(image: &RgbImage, x_range: Range<u32>, y_range: Range<u32>, stride: usize) -> f64
+{
+    let total = (x_range.len() * y_range.len()).div_ceil(stride).max(1) as f64;
 
-        this.current = this.current_raw();
+    y_range.flat_map(|y| x_range.clone().map(move |x| (x, y)))
+        .step_by(stride)
+        .map(|(x, y)| pixel_luminance(image.get_pixel(x, y)))
+        .sum::<f64>() / total
+}
 
-        this
-    }
+fn average_brightness(image: &RgbImage, center_weighted: bool, stride: usize) -> f64
+{
+    let (width, height) = image.dimensions();
 
-    fn info(&self) -> ControlInfo
+    let (x_range, y_range) = if center_weighted
     {
-        if let ControlValueDescription::IntegerRange{
-            min,
-            max,
-            value,
-            step,
-            default
-        } = self.control.as_ref().unwrap().description().clone()
-        {
-            ControlInfo{min, max, value, step, default}
-        } else
-        {
-            panic!("control must be an integer range")
-        }
-    }
+        (width / 3..width * 2 / 3, height / 3..height * 2 / 3)
+    } else
+    {
+        (0..width, 0..height)
+    };
 
-    pub fn clamp(&self, value: i64) -> i64
+    lightness(region_luminance(image, x_range, y_range, stride))
+}
+
+fn grid_brightness(image: &RgbImage, cells: u32, stride: usize) -> f64
+{
+    let (width, height) = image.dimensions();
+
+    let cell_lightness = (0..cells * cells).map(|cell|
     {
-        let info = self.info();
-        value.clamp(info.min, info.max)
-    }
+        let (cell_x, cell_y) = (cell % cells, cell / cells);
+
+        let x_range = width * cell_x / cells..width * (cell_x + 1) / cells;
+        let y_range = height * cell_y / cells..height * (cell_y + 1) / cells;
 
-    fn current_raw(&self) -> i64
+        lightness(region_luminance(image, x_range, y_range, stride))
+    });
+
+    let (darkest, brightest) = cell_lightness.fold((f64::MAX, f64::MIN), |(low, high), x|
     {
-        self.info().value
-    }
+        (low.min(x), high.max(x))
+    });
 
-    pub fn current(&self) -> i64
+    (darkest + brightest) / 2.0
+}
+
+// mccamy's approximation from the average chromaticity
+fn color_temperature(image: &RgbImage) -> Option<f64>
+{
+    let samples: Vec<[f64; 3]> = image.pixels().step_by(16).map(|Rgb(pixel)| pixel.map(srgb_to_linear)).collect();
+
+    let total = samples.len().max(1) as f64;
+    let [r, g, b] = samples.into_iter().fold([0.0; 3], |acc, pixel|
     {
-        self.current
-    }
+        [acc[0] + pixel[0], acc[1] + pixel[1], acc[2] + pixel[2]]
+    }).map(|x| x / total);
 
-    pub fn reset(&mut self, camera: &mut Camera)
+    let x = 0.4124 * r + 0.3576 * g + 0.1805 * b;
+    let y = 0.2126 * r + 0.7152 * g + 0.0722 * b;
+    let z = 0.0193 * r + 0.1192 * g + 0.9505 * b;
+
+    let sum = x + y + z;
+    if sum <= 0.0
     {
-        let value = self.info().default;
-        self.set(camera, value)
+        return None;
     }
 
-    pub fn set_max(&mut self, camera: &mut Camera)
+    let n = (x / sum - 0.3320) / (0.1858 - y / sum);
+
+    let temperature = 449.0 * n.powi(3) + 3525.0 * n.powi(2) + 6823.3 * n + 5520.33;
+
+    (temperature.is_finite() && temperature > 0.0).then_some(temperature)
+}
+
+// downsampled lightness plane tagged with the frame size
+fn motion_plane(image: &RgbImage) -> ((u32, u32), Vec<f32>)
+{
+    let plane = image.rows().step_by(MOTION_DOWNSCALE).flat_map(|row|
     {
-        let value = self.info().max;
-        self.set(camera, value)
-    }
+        row.step_by(MOTION_DOWNSCALE).map(|pixel| lightness(pixel_luminance(pixel)) as f32)
+    }).collect();
 
-    pub fn set(&mut self, camera: &mut Camera, value: i64)
+    (image.dimensions(), plane)
+}
+
+fn motion_amount(previous: &[f32], current: &[f32]) -> f64
+{
+    let total = current.len().max(1) as f64;
+
+    previous.iter().zip(current).map(|(a, b)| (a - b).abs() as f64).sum::<f64>() / total
+}
+
+fn luminance_histogram(image: &RgbImage) -> Box<[u32; 256]>
+{
+    let mut histogram = Box::new([0; 256]);
+
+    image.pixels().for_each(|pixel|
     {
-        if self.control.is_none()
-        {
-            return;
-        }
+        let index = (lightness(pixel_luminance(pixel)) / 100.0 * 255.0).round() as usize;
 
-        let value = self.clamp(value);
+        histogram[index.min(255)] += 1;
+    });
 
-        if value == self.current
-        {
-            return;
-        }
+    histogram
+}
 
-        self.current = value;
+fn channel_histograms(image: &RgbImage) -> Box<[[u32; 256]; 3]>
+{
+    let mut histograms = Box::new([[0; 256]; 3]);
 
-        let value = ControlValueSetter::Integer(value);
-        if let Err(err) = camera.set_camera_control(self.which, value)
+    image.pixels().for_each(|Rgb(pixel)|
+    {
+        histograms.iter_mut().zip(pixel).for_each(|(histogram, value)| histogram[*value as usize] += 1);
+    });
+
+    histograms
+}
+
+fn levels_range(image: &RgbImage, fraction: f64) -> (u8, u8)
+{
+    let histogram = luminance_histogram(image);
+
+    let total: u32 = histogram.iter().sum();
+    let cutoff = (total as f64 * fraction) as u32;
+
+    fn find_bin<'a>(mut bins: impl Iterator<Item=(usize, &'a u32)>, cutoff: u32) -> usize
+    {
+        let mut accumulated = 0;
+
+        bins.find(|(_, count)|
         {
-            eprintln!("error setting control: {err}");
-        }
+            accumulated += **count;
+
+            accumulated > cutoff
+        }).map(|(index, _)| index).unwrap_or(0)
     }
+
+    let low = find_bin(histogram.iter().enumerate(), cutoff);
+    let high = find_bin(histogram.iter().enumerate().rev(), cutoff);
+
+    let to_value = |bin: usize| linear_to_srgb(lightness_to_luminance(bin as f64 / 255.0 * 100.0));
+
+    (to_value(low), to_value(high))
 }
 
-enum CropControl
+fn luminance_waveform(image: &RgbImage) -> Box<[u32]>
 {
-    ZoomXPlus,
-    ZoomXMinus,
-    ZoomYPlus,
-    ZoomYMinus,
-    ShiftXPlus,
-    ShiftXMinus,
-    ShiftYPlus,
-    ShiftYMinus,
-    Length
+    let mut waveform = vec![0; WAVEFORM_COLUMNS * WAVEFORM_ROWS].into_boxed_slice();
+
+    let width = image.width().max(1) as usize;
+
+    image.enumerate_pixels().for_each(|(x, _, pixel)|
+    {
+        let column = x as usize * WAVEFORM_COLUMNS / width;
+        let row = (lightness(pixel_luminance(pixel)) / 100.0 * (WAVEFORM_ROWS - 1) as f64).round() as usize;
+
+        waveform[row.min(WAVEFORM_ROWS - 1) * WAVEFORM_COLUMNS + column] += 1;
+    });
+
+    waveform
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
-struct CropInfo
+fn overlay_area(area: Rect) -> Rect
 {
-    pub scale_x: f32,
-    pub scale_y: f32,
-    pub pos_x: f32,
-    pub pos_y: f32
+    let width = (area.width() / 4).max(1);
+    let height = (area.height() / 5).max(1);
+
+    let margin = (area.height() / 40) as i32;
+
+    Rect::new(area.x() + margin, area.bottom() - margin - height as i32, width, height)
 }
 
-impl CropInfo
+fn draw_rgb_histogram(canvas: &mut WindowCanvas, histograms: &[[u32; 256]; 3], area: Rect)
 {
-    pub fn new() -> Self
+    let overlay = overlay_area(area);
+
+    canvas.set_blend_mode(BlendMode::Blend);
+
+    canvas.set_draw_color(Color::RGBA(0, 0, 0, 120));
+    if let Err(err) = canvas.fill_rect(overlay)
     {
-        Self{
-            scale_x: 1.0,
-            scale_y: 1.0,
-            pos_x: 0.5,
-            pos_y: 0.5
-        }
+        eprintln!("error drawing histogram: {err}");
     }
-}
 
-#[derive(Debug, PartialEq, Eq)]
-enum GammaMode
-{
-    Manual{fullbright: bool, current: i64},
-    Auto
+    let highest = histograms.iter().flatten().copied().max().unwrap_or(0).max(1);
+
+    let colors = [Color::RGBA(255, 60, 60, 220), Color::RGBA(60, 255, 60, 220), Color::RGBA(60, 60, 255, 220)];
+
+    histograms.iter().zip(colors).for_each(|(histogram, color)|
+    {
+        let points: Vec<Point> = histogram.iter().enumerate().map(|(index, &count)|
+        {
+            let x = (index as u32 * overlay.width() / (histogram.len() as u32 - 1).max(1)) as i32;
+            let height = (count as f64 / highest as f64 * overlay.height() as f64) as i32;
+
+            Point::new(overlay.x() + x, overlay.bottom() - height)
+        }).collect();
+
+        canvas.set_draw_color(color);
+        if let Err(err) = canvas.draw_lines(points.as_slice())
+        {
+            eprintln!("error drawing histogram: {err}");
+        }
+    });
 }
 
-fn main()
+fn draw_histogram(canvas: &mut WindowCanvas, histogram: &[u32; 256], area: Rect)
 {
-    let camera_format = RequestedFormat::new::<RgbFormat>(RequestedFormatType::AbsoluteHighestResolution);
-    let mut camera = (0..10).filter_map(|i| Camera::new(CameraIndex::Index(i), camera_format).ok())
-        .next()
-        .unwrap_or_else(|| panic!("couldnt find a camera"));
+    let overlay = overlay_area(area);
 
-    let update_fps = (camera.frame_rate() * 2).max(UPDATE_FPS);
+    canvas.set_blend_mode(BlendMode::Blend);
 
-    let mut gamma_control = ControlController::new(&camera, KnownCameraControl::Gamma);
-    let mut brightness_control = ControlController::new(&camera, KnownCameraControl::Brightness);
+    canvas.set_draw_color(Color::RGBA(0, 0, 0, 120));
+    if let Err(err) = canvas.fill_rect(overlay)
+    {
+        eprintln!("error drawing histogram: {err}");
+    }
 
-    let Resolution{width_x: width, height_y: height} = camera.camera_format().resolution();
+    let highest = histogram.iter().copied().max().unwrap_or(0).max(1);
 
-    camera.open_stream().unwrap();
+    let bars: Vec<Rect> = histogram.iter().enumerate().filter_map(|(index, &count)|
+    {
+        let index = index as u32;
+        let bins = histogram.len() as u32;
 
-    let mut gamma_mode = GammaMode::Manual{fullbright: false, current: gamma_control.current()};
+        let start = (index * overlay.width() / bins) as i32;
+        let end = ((index + 1) * overlay.width() / bins) as i32;
 
-    let mut averager: Averager<5> = Averager::new();
-    let target_brightness = 15.0;
-    let brightness_range = 10.0;
+        let bar_height = (count as f64 / highest as f64 * overlay.height() as f64) as u32;
 
-    let mut mirrored = false;
+        (bar_height > 0).then(||
+        {
+            Rect::new(
+                overlay.x() + start,
+                overlay.bottom() - bar_height as i32,
+                (end - start).max(1) as u32,
+                bar_height
+            )
+        })
+    }).collect();
 
-    let mut title_delay = 0;
+    canvas.set_draw_color(Color::RGBA(255, 255, 255, 180));
+    if let Err(err) = canvas.fill_rects(&bars)
+    {
+        eprintln!("error drawing histogram: {err}");
+    }
+}
 
-    let mut resized = false;
-    let mut last_frame = Instant::now();
+fn draw_waveform(canvas: &mut WindowCanvas, waveform: &[u32], area: Rect)
+{
+    let histogram_area = overlay_area(area);
+    let overlay = Rect::new(
+        area.right() - (histogram_area.x() - area.x()) - histogram_area.width() as i32,
+        histogram_area.y(),
+        histogram_area.width(),
+        histogram_area.height()
+    );
+
+    canvas.set_blend_mode(BlendMode::Blend);
+
+    canvas.set_draw_color(Color::RGBA(0, 0, 0, 120));
+    if let Err(err) = canvas.fill_rect(overlay)
+    {
+        eprintln!("error drawing waveform: {err}");
+    }
 
-    let (tx, rx) = mpsc::channel();
+    let highest = waveform.iter().copied().max().unwrap_or(0).max(1);
 
-    let slow_events = Arc::new(Mutex::new(Vec::new()));
+    let shades = 8;
+    let mut cells = vec![Vec::new(); shades];
 
-    let input_thread = {
-        let slow_events = slow_events.clone();
+    waveform.iter().enumerate().filter(|(_, count)| **count > 0).for_each(|(index, &count)|
+    {
+        let (column, row) = ((index % WAVEFORM_COLUMNS) as u32, (index / WAVEFORM_COLUMNS) as u32);
 
-        thread::spawn(move ||
+        let start_x = (column * overlay.width() / WAVEFORM_COLUMNS as u32) as i32;
+        let end_x = ((column + 1) * overlay.width() / WAVEFORM_COLUMNS as u32) as i32;
+
+        let start_y = (row * overlay.height() / WAVEFORM_ROWS as u32) as i32;
+        let end_y = ((row + 1) * overlay.height() / WAVEFORM_ROWS as u32) as i32;
+
+        let intensity = (count as f64 / highest as f64).sqrt();
+        let shade = ((intensity * shades as f64) as usize).min(shades - 1);
+
+        cells[shade].push(Rect::new(
+            overlay.x() + start_x,
+            overlay.bottom() - end_y,
+            (end_x - start_x).max(1) as u32,
+            (end_y - start_y).max(1) as u32
+        ));
+    });
+
+    cells.iter().enumerate().for_each(|(shade, rects)|
+    {
+        let alpha = (shade + 1) * 255 / shades;
+
+        canvas.set_draw_color(Color::RGBA(255, 255, 255, alpha as u8));
+        if let Err(err) = canvas.fill_rects(rects)
         {
-            let ctx = sdl2::init().unwrap();
+            eprintln!("error drawing waveform: {err}");
+        }
+    });
+}
 
-            let video = ctx.video().unwrap();
-            video.enable_screen_saver();
+fn draw_crosshair(canvas: &mut WindowCanvas, color: Color, area: Rect)
+{
+    let center = area.center();
 
-            let window = video.window("cam", width, height)
-                .always_on_top()
-                .resizable()
-                .build()
-                .unwrap();
+    canvas.set_draw_color(color);
 
-            let mut canvas = window.into_canvas().build().unwrap();
+    let horizontal = canvas.draw_line((area.left(), center.y()), (area.right() - 1, center.y()));
+    let vertical = canvas.draw_line((center.x(), area.top()), (center.x(), area.bottom() - 1));
 
-            canvas.clear();
-            canvas.present();
+    if let Err(err) = horizontal.and(vertical)
+    {
+        eprintln!("error drawing crosshair: {err}");
+    }
+}
 
-            let mut events = ctx.event_pump().unwrap();
+fn draw_grid(canvas: &mut WindowCanvas, area: Rect)
+{
+    canvas.set_blend_mode(BlendMode::Blend);
+    canvas.set_draw_color(Color::RGBA(255, 255, 255, 100));
 
-            let texture_creator = canvas.texture_creator();
-            let mut camera_texture: Option<Texture> = None;
+    let result = (1..3).try_for_each(|i|
+    {
+        let x = area.x() + (area.width() * i / 3) as i32;
+        let y = area.y() + (area.height() * i / 3) as i32;
 
-            let mut crop_info = CropInfo::new();
-            let mut crop_controls = [false; CropControl::Length as usize];
+        canvas.draw_line((x, area.top()), (x, area.bottom() - 1))?;
+        canvas.draw_line((area.left(), y), (area.right() - 1, y))
+    });
 
-            fn crop_control_of(
-                code: Keycode,
-                keymod: Mod,
-                ignore_shift: bool,
-                mut f: impl FnMut(CropControl) -> bool
-            ) -> bool
-            {
-                let is_shift = keymod == Mod::LSHIFTMOD;
+    if let Err(err) = result
+    {
+        eprintln!("error drawing grid: {err}");
+    }
+}
 
-                match code
-                {
+fn draw_text(canvas: &mut WindowCanvas, text: &str, x: i32, y: i32, scale: u32)
+{
+    let mut rects = Vec::new();
+    font::for_each_pixel(text, |pixel_x, pixel_y|
+    {
+        rects.push(Rect::new(
+            x + (pixel_x * scale) as i32,
+            y + (pixel_y * scale) as i32,
+            scale,
+            scale
+        ));
+    });
+
+    if let Err(err) = canvas.fill_rects(&rects)
+    {
+        eprintln!("error drawing text: {err}");
+    }
+}
+
+fn draw_help(canvas: &mut WindowCanvas, lines: &[String], area: Rect)
+{
+    let scale = (area.height() / 400).max(1);
+
+    let padding = (font::GLYPH_WIDTH * scale) as i32;
+    let line_height = ((font::GLYPH_HEIGHT + 3) * scale) as i32;
+
+    let text_width = lines.iter().map(|line| font::text_size(line).0).max().unwrap_or(0) * scale;
+    let text_height = line_height * lines.len() as i32;
+
+    let background = Rect::new(
+        area.x() + padding,
+        area.y() + padding,
+        text_width + padding as u32 * 2,
+        (text_height + padding * 2) as u32
+    );
+
+    canvas.set_blend_mode(BlendMode::Blend);
+
+    canvas.set_draw_color(Color::RGBA(0, 0, 0, 200));
+    if let Err(err) = canvas.fill_rect(background)
+    {
+        eprintln!("error drawing help: {err}");
+    }
+
+    canvas.set_draw_color(Color::RGBA(255, 255, 255, 255));
+    lines.iter().enumerate().for_each(|(index, line)|
+    {
+        let x = background.x() + padding;
+        let y = background.y() + padding + line_height * index as i32;
+
+        draw_text(canvas, line, x, y, scale);
+    });
+}
+
+fn draw_status(canvas: &mut WindowCanvas, text: &str, area: Rect)
+{
+    let scale = (area.height() / 400).max(1);
+
+    let padding = (2 * scale) as i32;
+    let height = font::GLYPH_HEIGHT * scale + padding as u32 * 2;
+
+    let strip = Rect::new(area.x(), area.bottom() - height as i32, area.width(), height);
+
+    canvas.set_blend_mode(BlendMode::Blend);
+
+    canvas.set_draw_color(Color::RGBA(0, 0, 0, 180));
+    if let Err(err) = canvas.fill_rect(strip)
+    {
+        eprintln!("error drawing status bar: {err}");
+    }
+
+    canvas.set_draw_color(Color::RGBA(255, 255, 255, 255));
+    draw_text(canvas, text, strip.x() + padding * 2, strip.y() + padding, scale);
+}
+
+fn validated_arg(name: &str, value: Option<f64>, range: RangeInclusive<f64>, default: f64) -> f64
+{
+    match value
+    {
+        Some(value) if range.contains(&value) => value,
+        Some(value) =>
+        {
+            eprintln!(
+                "{name} {value} is outside of {}..={}, using {default}",
+                range.start(),
+                range.end()
+            );
+
+            default
+        },
+        None => default
+    }
+}
+
+fn set_frame_rate(camera: &mut Camera, fps: Option<u32>)
+{
+    if let Some(fps) = fps
+    {
+        if let Err(err) = camera.set_frame_rate(fps)
+        {
+            eprintln!("error setting framerate: {err}");
+        }
+
+        println!("using framerate {}", camera.camera_format().frame_rate());
+    }
+}
+
+// replaces {name} placeholders, unknown ones are kept as they are
+fn format_title(format: &str, values: &[(&str, String)]) -> String
+{
+    let mut title = String::new();
+
+    let mut rest = format;
+    while let Some(start) = rest.find('{')
+    {
+        title += &rest[..start];
+        rest = &rest[start..];
+
+        let value = rest.find('}').and_then(|end|
+        {
+            let name = &rest[1..end];
+
+            values.iter().find(|(key, _)| *key == name).map(|(_, value)| (end, value))
+        });
+
+        if let Some((end, value)) = value
+        {
+            title += value;
+            rest = &rest[end + 1..];
+        } else
+        {
+            title.push('{');
+            rest = &rest[1..];
+        }
+    }
+
+    title + rest
+}
+
+fn format_description(camera: &Camera) -> String
+{
+    let format = camera.camera_format();
+
+    format!("{} {} fps {}", format.resolution(), format.frame_rate(), format.format())
+}
+
+fn sorted_resolutions(camera: &mut Camera) -> Option<Vec<Resolution>>
+{
+    let mut resolutions: Vec<Resolution> = camera.compatible_camera_formats().ok()?
+        .into_iter()
+        .map(|format| format.resolution())
+        .collect();
+
+    resolutions.sort();
+    resolutions.dedup();
+
+    Some(resolutions)
+}
+
+fn next_resolution(camera: &mut Camera) -> Option<Resolution>
+{
+    let resolutions = sorted_resolutions(camera)?;
+
+    let current = camera.resolution();
+
+    resolutions.iter().rev().find(|resolution| **resolution < current).or(resolutions.last()).copied()
+}
+
+fn adjacent_resolution(camera: &mut Camera, lower: bool) -> Option<Resolution>
+{
+    let resolutions = sorted_resolutions(camera)?;
+
+    let current = camera.resolution();
+
+    if lower
+    {
+        resolutions.into_iter().rev().find(|resolution| *resolution < current)
+    } else
+    {
+        resolutions.into_iter().find(|resolution| *resolution > current)
+    }
+}
+
+fn switch_resolution(camera: &mut Camera, resolution: Resolution) -> Result<(), NokhwaError>
+{
+    let previous = camera.camera_format();
+
+    let set_format = |camera: &mut Camera, format: CameraFormat|
+    {
+        let request = RequestedFormat::new::<RgbFormat>(RequestedFormatType::Closest(format));
+
+        camera.stop_stream()?;
+        camera.set_camera_requset(request)?;
+        camera.open_stream()
+    };
+
+    let format = CameraFormat::new(resolution, previous.format(), previous.frame_rate());
+    set_format(camera, format).inspect_err(|_|
+    {
+        if let Err(err) = set_format(camera, previous)
+        {
+            eprintln!("error restoring the previous format: {err}");
+        }
+    })
+}
+
+fn open_camera(index: u32, format: RequestedFormat, fps: Option<u32>) -> Result<Camera, NokhwaError>
+{
+    let mut camera = Camera::new(CameraIndex::Index(index), format)?;
+
+    set_frame_rate(&mut camera, fps);
+
+    camera.open_stream()?;
+
+    Ok(camera)
+}
+
+fn list_cameras(format: RequestedFormat)
+{
+    (0..10).for_each(|index|
+    {
+        let Ok(mut camera) = Camera::new(CameraIndex::Index(index), format) else
+        {
+            return;
+        };
+
+        let info = camera.info().clone();
+
+        let highest = camera.compatible_camera_formats().ok().and_then(|formats|
+        {
+            formats.into_iter().map(|format| format.resolution()).max()
+        });
+
+        let highest = highest.map(|resolution| resolution.to_string()).unwrap_or_else(|| "unknown".to_owned());
+
+        println!("{index}: {} ({}), highest resolution {highest}", info.human_name(), info.description());
+    });
+}
+
+fn print_controls(camera: &Camera)
+{
+    all_known_camera_controls().into_iter().for_each(|which|
+    {
+        let Ok(control) = camera.camera_control(which) else
+        {
+            return;
+        };
+
+        let description = control.description();
+        if let Some(info) = ControlInfo::from_description(description)
+        {
+            println!(
+                "{which}: {}..={} step {}, default {}, current {}",
+                info.min,
+                info.max,
+                info.step,
+                info.default,
+                info.value
+            );
+        } else
+        {
+            println!("{which}: {description}");
+        }
+    });
+}
+
+// clockwise rotation that makes the image upright
+fn sensor_rotation(camera: &Camera) -> Option<u16>
+{
+    let control = camera.camera_control(KnownCameraControl::Other(SENSOR_ROTATION_CONTROL)).ok()?;
+
+    let degrees = control.description().value().as_integer()?.rem_euclid(360);
+
+    (degrees % 90 == 0).then(|| ((360 - degrees) % 360) as u16)
+}
+
+fn unix_millis() -> u128
+{
+    SystemTime::now().duration_since(UNIX_EPOCH)
+        .map(|x| x.as_millis())
+        .unwrap_or(0)
+}
+
+// pngs are lossless, so quality only picks how hard to compress, the default of 80 keeps the usual level
+fn encode_png(filename: &str, image: &RgbImage, quality: u8) -> ImageResult<()>
+{
+    let file = BufWriter::new(File::create(filename)?);
+
+    let compression = match quality
+    {
+        0..=33 => CompressionType::Fast,
+        34..=89 => CompressionType::Default,
+        _ => CompressionType::Best
+    };
+
+    PngEncoder::new_with_quality(file, compression, FilterType::Adaptive).write_image(
+        image.as_raw(),
+        image.width(),
+        image.height(),
+        ExtendedColorType::Rgb8
+    )
+}
+
+fn save_image(image: &RgbImage, kind: &str, filename: &str, quality: u8)
+{
+    match encode_png(filename, image, quality)
+    {
+        Ok(()) => println!("saved {kind} to {filename}"),
+        Err(err) => eprintln!("error saving {kind}: {err}")
+    }
+}
+
+fn save_snapshot(image: &RgbImage, quality: u8)
+{
+    save_image(image, "snapshot", &format!("snapshot_{}.png", unix_millis()), quality);
+}
+
+// hands a png over to whichever clipboard tool is installed
+fn copy_to_clipboard(image: &RgbImage) -> bool
+{
+    let mut data = Vec::new();
+    if let Err(err) = image.write_to(&mut Cursor::new(&mut data), ImageFormat::Png)
+    {
+        eprintln!("error encoding a clipboard snapshot: {err}");
+        return false;
+    }
+
+    let tools: [(&str, &[&str]); 2] = [
+        ("wl-copy", &["--type", "image/png"]),
+        ("xclip", &["-selection", "clipboard", "-t", "image/png"])
+    ];
+
+    tools.into_iter().any(|(tool, args)|
+    {
+        let Ok(mut child) = Command::new(tool).args(args).stdin(Stdio::piped()).spawn() else
+        {
+            return false;
+        };
+
+        let written = child.stdin.take().map(|mut stdin| stdin.write_all(&data).is_ok()).unwrap_or(false);
+
+        child.wait().map(|status| status.success()).unwrap_or(false) && written
+    })
+}
+
+fn encode_gif(filename: &str, frames: Vec<RgbImage>, frametime: f64, quality: u8) -> ImageResult<()>
+{
+    let file = BufWriter::new(File::create(filename)?);
+
+    let speed = 1 + (100 - quality.min(100) as i32) * 29 / 100;
+
+    let mut encoder = GifEncoder::new_with_speed(file, speed);
+    encoder.set_repeat(Repeat::Infinite)?;
+
+    let delay = Delay::from_numer_denom_ms((frametime.round() as u32).max(1), 1);
+
+    encoder.encode_frames(frames.into_iter().map(|frame|
+    {
+        Frame::from_parts(DynamicImage::from(frame).to_rgba8(), 0, 0, delay)
+    }))
+}
+
+fn save_recording(
+    name: &'static str,
+    frames: Vec<RgbImage>,
+    frametime: f64,
+    quality: u8
+) -> thread::JoinHandle<()>
+{
+    let filename = format!("{name}_{}.gif", unix_millis());
+
+    thread::spawn(move ||
+    {
+        match encode_gif(&filename, frames, frametime, quality)
+        {
+            Ok(()) => println!("saved {name} to {filename}"),
+            Err(err) => eprintln!("error saving {name}: {err}")
+        }
+    })
+}
+
+fn save_burst(frames: Vec<RgbImage>, quality: u8) -> thread::JoinHandle<()>
+{
+    // each burst gets its own directory so the frames can keep plain numbered names
+    let directory = format!("burst_{}", unix_millis());
+
+    thread::spawn(move ||
+    {
+        if let Err(err) = fs::create_dir_all(&directory)
+        {
+            eprintln!("error creating burst directory {directory}: {err}");
+            return;
+        }
+
+        frames.iter().enumerate().for_each(|(index, frame)|
+        {
+            save_image(frame, "burst frame", &format!("{directory}/burst_{index:03}.png"), quality);
+        });
+    })
+}
+
+fn letterbox_rect((width, height): (u32, u32), aspect: f64) -> Rect
+{
+    let (fitted_width, fitted_height) = if height as f64 * aspect > width as f64
+    {
+        (width, (width as f64 / aspect) as u32)
+    } else
+    {
+        ((height as f64 * aspect) as u32, height)
+    };
+
+    Rect::new(
+        ((width - fitted_width) / 2) as i32,
+        ((height - fitted_height) / 2) as i32,
+        fitted_width,
+        fitted_height
+    )
+}
+
+struct Averager
+{
+    window: VecDeque<f64>
+}
+
+impl Averager
+{
+    pub fn new(size: usize) -> Self
+    {
+        Self{window: VecDeque::from(vec![0.0; size.max(1)])}
+    }
+
+    pub fn add(&mut self, value: f64) -> f64
+    {
+        self.window.pop_front();
+        self.window.push_back(value);
+
+        self.average()
+    }
+
+    pub fn average(&self) -> f64
+    {
+        self.window.iter().copied().sum::<f64>() / self.window.len() as f64
+    }
+}
+
+// the output is a fraction of the controlled range, so the same gains work
+// regardless of how many steps the camera exposes
+struct PiController
+{
+    proportional: f64,
+    integral: f64,
+    integral_limit: f64,
+    accumulated: f64
+}
+
+impl PiController
+{
+    pub fn new(proportional: f64, integral: f64, integral_limit: f64) -> Self
+    {
+        Self{proportional, integral, integral_limit, accumulated: 0.0}
+    }
+
+    pub fn reset(&mut self)
+    {
+        self.accumulated = 0.0;
+    }
+
+    pub fn update(&mut self, error: f64) -> f64
+    {
+        self.accumulated = (self.accumulated + error).clamp(-self.integral_limit, self.integral_limit);
+
+        error * self.proportional + self.accumulated * self.integral
+    }
+}
+
+#[derive(Debug)]
+enum CaptureError
+{
+    Frame(NokhwaError),
+    Decode(NokhwaError)
+}
+
+enum CapturedFrame
+{
+    Rgb(RgbImage),
+    Yuyv(Buffer)
+}
+
+#[derive(Debug, Clone)]
+enum FrameData
+{
+    Rgb(Box<RgbImage>),
+    Yuyv{width: u32, height: u32, data: Vec<u8>}
+}
+
+impl FrameData
+{
+    fn size(&self) -> (u32, u32)
+    {
+        match self
+        {
+            Self::Rgb(image) => image.dimensions(),
+            Self::Yuyv{width, height, ..} => (*width, *height)
+        }
+    }
+
+    fn pixel_format(&self) -> PixelFormatEnum
+    {
+        match self
+        {
+            Self::Rgb(_) => PixelFormatEnum::RGB24,
+            Self::Yuyv{..} => PixelFormatEnum::YUY2
+        }
+    }
+
+    fn pitch(&self) -> usize
+    {
+        let bytes_per_pixel = match self
+        {
+            Self::Rgb(_) => 3,
+            Self::Yuyv{..} => 2
+        };
+
+        self.size().0 as usize * bytes_per_pixel
+    }
+
+    fn into_raw(self) -> Vec<u8>
+    {
+        match self
+        {
+            Self::Rgb(image) => image.into_raw(),
+            Self::Yuyv{data, ..} => data
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct Overlays
+{
+    help: Option<Vec<String>>,
+    histogram: Option<Box<[u32; 256]>>,
+    rgb_histogram: Option<Box<[[u32; 256]; 3]>>,
+    waveform: Option<Box<[u32]>>,
+    crosshair: Option<Color>,
+    grid: bool,
+    status: Option<String>
+}
+
+#[derive(Debug, Clone)]
+enum ProgramMessage
+{
+    Render(FrameData, Overlays),
+    ResetWindow,
+    ResizeWindow(u32, u32),
+    ToggleFullscreen,
+    SetAlwaysOnTop(bool),
+    SetClosestAspect,
+    SetTitle(String),
+    SetScaleMode(ScaleMode),
+    Beep
+}
+
+#[derive(Debug, Clone, Copy)]
+struct ControlInfo
+{
+    pub min: i64,
+    pub max: i64,
+    pub value: i64,
+    pub step: i64,
+    pub default: i64
+}
+
+impl ControlInfo
+{
+    pub fn from_description(description: &ControlValueDescription) -> Option<Self>
+    {
+        if let ControlValueDescription::IntegerRange{
+            min,
+            max,
+            value,
+            step,
+            default
+        } = *description
+        {
+            Some(ControlInfo{min, max, value, step, default})
+        } else
+        {
+            None
+        }
+    }
+}
+
+struct ControlController
+{
+    info: Option<ControlInfo>,
+    current: i64,
+    which: KnownCameraControl
+}
+
+impl ControlController
+{
+    pub fn new(camera: &Camera, which: KnownCameraControl) -> Self
+    {
+        let info = camera.camera_control(which).ok().and_then(|control|
+        {
+            ControlInfo::from_description(control.description())
+        });
+
+        let current = info.map(|info| info.value).unwrap_or(0);
+
+        Self{info, current, which}
+    }
+
+    pub fn clamp(&self, value: i64) -> i64
+    {
+        if let Some(info) = self.info
+        {
+            value.clamp(info.min, info.max)
+        } else
+        {
+            value
+        }
+    }
+
+    pub fn is_supported(&self) -> bool
+    {
+        self.info.is_some()
+    }
+
+    pub fn range(&self) -> i64
+    {
+        let (min, max) = self.limits();
+        max - min
+    }
+
+    pub fn limits(&self) -> (i64, i64)
+    {
+        self.info.map(|info| (info.min, info.max)).unwrap_or((0, 0))
+    }
+
+    pub fn can_move(&self, direction: i64) -> bool
+    {
+        if !self.is_supported()
+        {
+            return false;
+        }
+
+        let (min, max) = self.limits();
+
+        if direction > 0
+        {
+            self.current < max
+        } else
+        {
+            self.current > min
+        }
+    }
+
+    pub fn step(&self) -> i64
+    {
+        self.info.map(|info| info.step.max(1)).unwrap_or(1)
+    }
+
+    pub fn current(&self) -> i64
+    {
+        self.current
+    }
+
+    pub fn reset(&mut self, camera: &mut Camera)
+    {
+        if let Some(info) = self.info
+        {
+            self.set(camera, info.default)
+        }
+    }
+
+    pub fn set_max(&mut self, camera: &mut Camera)
+    {
+        if let Some(info) = self.info
+        {
+            self.set(camera, info.max)
+        }
+    }
+
+    pub fn step_by(&mut self, camera: &mut Camera, direction: i64)
+    {
+        let value = self.current + self.step() * direction;
+        self.set(camera, value)
+    }
+
+    pub fn set(&mut self, camera: &mut Camera, value: i64)
+    {
+        if !self.is_supported()
+        {
+            return;
+        }
+
+        let value = self.clamp(value);
+
+        if value == self.current
+        {
+            return;
+        }
+
+        self.current = value;
+
+        let value = ControlValueSetter::Integer(value);
+        if let Err(err) = camera.set_camera_control(self.which, value)
+        {
+            eprintln!("error setting control: {err}");
+        }
+    }
+}
+
+struct SwitchControl
+{
+    default: Option<bool>,
+    current: bool,
+    integer: bool,
+    which: KnownCameraControl
+}
+
+impl SwitchControl
+{
+    pub fn new(camera: &Camera, which: KnownCameraControl) -> Self
+    {
+        let description = camera.camera_control(which).ok().map(|control| control.description().clone());
+
+        let (default, current, integer) = match description
+        {
+            Some(ControlValueDescription::Boolean{value, default}) => (Some(default), value, false),
+            Some(ControlValueDescription::IntegerRange{min: 0, max: 1, value, default, ..}) =>
+            {
+                (Some(default != 0), value != 0, true)
+            },
+            _ => (None, false, false)
+        };
+
+        Self{default, current, integer, which}
+    }
+
+    pub fn is_supported(&self) -> bool
+    {
+        self.default.is_some()
+    }
+
+    pub fn current(&self) -> bool
+    {
+        self.current
+    }
+
+    pub fn reset(&mut self, camera: &mut Camera)
+    {
+        if let Some(default) = self.default
+        {
+            self.set(camera, default);
+        }
+    }
+
+    pub fn set(&mut self, camera: &mut Camera, value: bool)
+    {
+        if !self.is_supported() || value == self.current
+        {
+            return;
+        }
+
+        self.current = value;
+
+        let value = if self.integer
+        {
+            ControlValueSetter::Integer(value as i64)
+        } else
+        {
+            ControlValueSetter::Boolean(value)
+        };
+
+        if let Err(err) = camera.set_camera_control(self.which, value)
+        {
+            eprintln!("error setting control: {err}");
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ControlKind
+{
+    Gamma,
+    Brightness,
+    Contrast,
+    Saturation,
+    Exposure,
+    WhiteBalance,
+    Gain,
+    Focus,
+    Zoom,
+    Pan,
+    Tilt
+}
+
+impl ControlKind
+{
+    fn name(&self) -> &'static str
+    {
+        match self
+        {
+            Self::Gamma => "gamma",
+            Self::Brightness => "brightness",
+            Self::Contrast => "contrast",
+            Self::Saturation => "saturation",
+            Self::Exposure => "exposure",
+            Self::WhiteBalance => "white balance",
+            Self::Gain => "gain",
+            Self::Focus => "focus",
+            Self::Zoom => "zoom",
+            Self::Pan => "pan",
+            Self::Tilt => "tilt"
+        }
+    }
+}
+
+struct Controls
+{
+    gamma: ControlController,
+    brightness: ControlController,
+    contrast: ControlController,
+    saturation: ControlController,
+    exposure: ControlController,
+    white_balance: ControlController,
+    gain: ControlController,
+    focus: ControlController,
+    auto_focus: SwitchControl,
+    zoom: ControlController,
+    pan: ControlController,
+    tilt: ControlController
+}
+
+impl Controls
+{
+    pub fn new(camera: &Camera) -> Self
+    {
+        Self{
+            gamma: ControlController::new(camera, KnownCameraControl::Gamma),
+            brightness: ControlController::new(camera, KnownCameraControl::Brightness),
+            contrast: ControlController::new(camera, KnownCameraControl::Contrast),
+            saturation: ControlController::new(camera, KnownCameraControl::Saturation),
+            exposure: ControlController::new(camera, KnownCameraControl::Exposure),
+            white_balance: ControlController::new(camera, KnownCameraControl::WhiteBalance),
+            gain: ControlController::new(camera, KnownCameraControl::Gain),
+            focus: ControlController::new(camera, KnownCameraControl::Focus),
+            auto_focus: SwitchControl::new(camera, KnownCameraControl::Other(AUTO_FOCUS_CONTROL)),
+            zoom: ControlController::new(camera, KnownCameraControl::Zoom),
+            pan: ControlController::new(camera, KnownCameraControl::Pan),
+            tilt: ControlController::new(camera, KnownCameraControl::Tilt)
+        }
+    }
+
+    pub fn get_mut(&mut self, kind: ControlKind) -> &mut ControlController
+    {
+        match kind
+        {
+            ControlKind::Gamma => &mut self.gamma,
+            ControlKind::Brightness => &mut self.brightness,
+            ControlKind::Contrast => &mut self.contrast,
+            ControlKind::Saturation => &mut self.saturation,
+            ControlKind::Exposure => &mut self.exposure,
+            ControlKind::WhiteBalance => &mut self.white_balance,
+            ControlKind::Gain => &mut self.gain,
+            ControlKind::Focus => &mut self.focus,
+            ControlKind::Zoom => &mut self.zoom,
+            ControlKind::Pan => &mut self.pan,
+            ControlKind::Tilt => &mut self.tilt
+        }
+    }
+
+    pub fn reset(&mut self, camera: &mut Camera)
+    {
+        self.gamma.reset(camera);
+        self.brightness.reset(camera);
+        self.contrast.reset(camera);
+        self.saturation.reset(camera);
+        self.exposure.reset(camera);
+        self.white_balance.reset(camera);
+        self.gain.reset(camera);
+        self.auto_focus.reset(camera);
+        self.focus.reset(camera);
+        self.zoom.reset(camera);
+        self.pan.reset(camera);
+        self.tilt.reset(camera);
+    }
+}
+
+enum CropControl
+{
+    ZoomXPlus,
+    ZoomXMinus,
+    ZoomYPlus,
+    ZoomYMinus,
+    ShiftXPlus,
+    ShiftXMinus,
+    ShiftYPlus,
+    ShiftYMinus,
+    Length
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct CropInfo
+{
+    pub scale_x: f32,
+    pub scale_y: f32,
+    pub pos_x: f32,
+    pub pos_y: f32
+}
+
+impl CropInfo
+{
+    pub fn new() -> Self
+    {
+        Self{
+            scale_x: 1.0,
+            scale_y: 1.0,
+            pos_x: 0.5,
+            pos_y: 0.5
+        }
+    }
+
+    pub fn zoom(&mut self, factor: f32, low_zoom: f32)
+    {
+        self.scale_x = (self.scale_x * factor).clamp(low_zoom, 1.0);
+        self.scale_y = (self.scale_y * factor).clamp(low_zoom, 1.0);
+
+        self.pan(0.0, 0.0);
+    }
+
+    pub fn pan(&mut self, x: f32, y: f32)
+    {
+        let clamped = |value: f32, scale: f32|
+        {
+            let half_zoom = scale * 0.5;
+
+            value.clamp(half_zoom, 1.0 - half_zoom)
+        };
+
+        self.pos_x = clamped(self.pos_x + x, self.scale_x);
+        self.pos_y = clamped(self.pos_y + y, self.scale_y);
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum GammaMode
+{
+    Manual{fullbright: bool, current: i64},
+    Auto
+}
+
+impl GammaMode
+{
+    // after the camera changes, auto stays auto and manual follows the new camera
+    fn camera_changed(&mut self, current: i64)
+    {
+        if let Self::Manual{..} = self
+        {
+            *self = Self::Manual{fullbright: false, current};
+        }
+    }
+}
+
+// settings to go back to after night mode
+struct NightMode
+{
+    gamma_mode: GammaMode,
+    gamma: i64,
+    brightness: i64,
+    blurred: bool,
+    boost: GammaLut
+}
+
+// locks the shared camera only once something actually uses it
+struct CameraLock<'a>
+{
+    shared: &'a Mutex<Camera>,
+    guard: OnceCell<MutexGuard<'a, Camera>>
+}
+
+impl<'a> CameraLock<'a>
+{
+    fn new(shared: &'a Mutex<Camera>) -> Self
+    {
+        Self{shared, guard: OnceCell::new()}
+    }
+}
+
+impl Deref for CameraLock<'_>
+{
+    type Target = Camera;
+
+    fn deref(&self) -> &Camera
+    {
+        self.guard.get_or_init(|| self.shared.lock().unwrap())
+    }
+}
+
+impl DerefMut for CameraLock<'_>
+{
+    fn deref_mut(&mut self) -> &mut Camera
+    {
+        self.guard.get_or_init(|| self.shared.lock().unwrap());
+
+        self.guard.get_mut().unwrap()
+    }
+}
+
+// a gamma sweep looking for the value closest to the target brightness
+struct Calibration
+{
+    previous: i64,
+    values: Vec<i64>,
+    index: usize,
+    settle: u32,
+    best: Option<(i64, f64)>
+}
+
+fn spawn_camera_windows(cameras: &[u32], args: &Args)
+{
+    let executable = match env::current_exe()
+    {
+        Ok(x) => x,
+        Err(err) =>
+        {
+            eprintln!("couldnt find the executable: {err}");
+            process::exit(1)
+        }
+    };
+
+    let forwarded = args::forwarded();
+    let window_of = cameras.iter().map(|index| index.to_string()).collect::<Vec<_>>().join(",");
+
+    let children: Vec<_> = cameras.iter().enumerate().filter_map(|(nth, index)|
+    {
+        let mut command = process::Command::new(&executable);
+        command.args(&forwarded)
+            .args(["--camera", &index.to_string(), "--window-of", &window_of]);
+
+        if let Some(port) = args.serve
+        {
+            command.args(["--serve", &(port + nth as u16).to_string()]);
+        }
+
+        if let Some(path) = args.stats_log.as_ref()
+        {
+            let path = Path::new(path);
+
+            let mut name = path.file_stem().unwrap_or_default().to_owned();
+            name.push(format!("-camera{index}"));
+
+            let path = path.with_file_name(name).with_extension(path.extension().unwrap_or_default());
+
+            command.arg("--stats-log").arg(path);
+        }
+
+        if let Some(Position{x, y}) = args.position
+        {
+            let offset = nth as i32 * WINDOW_CASCADE;
+
+            command.args(["--position", &format!("{},{}", x + offset, y + offset)]);
+        }
+
+        let child = command.spawn();
+
+        match child
+        {
+            Ok(x) => Some(x),
+            Err(err) =>
+            {
+                eprintln!("couldnt start a window for camera {index}: {err}");
+
+                None
+            }
+        }
+    }).collect();
+
+    children.into_iter().for_each(|mut child|
+    {
+        if let Err(err) = child.wait()
+        {
+            eprintln!("error waiting for a camera window: {err}");
+        }
+    });
+}
+
+fn main()
+{
+    let args = Args::parse();
+
+    if let Some(CameraList(cameras)) = args.cameras.as_ref()
+    {
+        spawn_camera_windows(cameras, &args);
+
+        return;
+    }
+    // a window spawned by --cameras gets its own config and the already known camera list
+    let config_camera = args.window_of.as_ref().and(args.camera);
+
+    let mut config = Config::load(config_camera);
+
+    let requested_fps = args.fps.or(config.fps);
+    let save_fps = args.fps.is_none();
+
+    let requested_format = if let Some(Size{width, height}) = args.resolution
+    {
+        let format = CameraFormat::new(Resolution::new(width, height), FrameFormat::MJPEG, 30);
+
+        RequestedFormatType::Closest(format)
+    } else
+    {
+        RequestedFormatType::AbsoluteHighestResolution
+    };
+
+    let camera_format = RequestedFormat::new::<RgbFormat>(requested_format);
+
+    if args.list_cameras
+    {
+        list_cameras(camera_format);
+
+        return;
+    }
+
+    let camera_indices: Vec<u32> = if let Some(CameraList(cameras)) = args.window_of.as_ref()
+    {
+        cameras.clone()
+    } else
+    {
+        (0..10).filter(|&i|
+        {
+            Camera::new(CameraIndex::Index(i), camera_format).is_ok()
+        }).collect()
+    };
+
+    let (mut camera_index, mut camera) = if let Some(index) = args.camera
+    {
+        match Camera::new(CameraIndex::Index(index), camera_format)
+        {
+            Ok(x) => (index, x),
+            Err(err) =>
+            {
+                eprintln!("couldnt open camera {index}: {err}");
+                process::exit(1)
+            }
+        }
+    } else
+    {
+        camera_indices.iter().find_map(|&i|
+        {
+            Camera::new(CameraIndex::Index(i), camera_format).ok().map(|camera| (i, camera))
+        }).unwrap_or_else(|| panic!("couldnt find a camera"))
+    };
+
+    set_frame_rate(&mut camera, requested_fps);
+
+    if args.controls
+    {
+        print_controls(&camera);
+
+        return;
+    }
+
+    let camera_fps = camera.frame_rate();
+    let update_fps = (camera_fps * 2).max(UPDATE_FPS);
+
+    let adaptive_fps = args.adaptive_fps.filter(|fps| *fps > 0.0);
+    let adaptive_hysteresis = validated_arg("adaptive hysteresis", args.adaptive_hysteresis, 0.0..=60.0, 5.0);
+    let adaptive_time = Duration::from_secs(3);
+    let mut adaptive_since: Option<(bool, Instant)> = None;
+    let mut adaptive_ceiling: Option<Resolution> = None;
+    let mut resolution_request: Option<(Resolution, bool)> = None;
+
+    let mut controls = Controls::new(&camera);
+
+    let initial_rotation = sensor_rotation(&camera);
+    if let Some(rotation) = initial_rotation
+    {
+        println!("detected sensor orientation, rotating by {rotation}°");
+    }
+
+    let Resolution{width_x: width, height_y: height} = camera.camera_format().resolution();
+
+    println!("using resolution {width}x{height}");
+
+    camera.open_stream().unwrap();
+
+    println!("camera format: {}", camera.camera_format());
+
+    let verbose = args.verbose;
+    let mut format_text = format_description(&camera);
+
+    let mut camera_error: Option<String> = None;
+
+    let mut frame_errors = 0;
+    let mut dropped_frames: u64 = 0;
+    let mut dropped_yuyv: u64 = 0;
+    let mut yuyv_seen = false;
+    let mut reconnecting = false;
+
+    if let Some(gamma) = config.gamma
+    {
+        controls.gamma.set(&mut camera, gamma);
+    }
+
+    let mut gamma_mode = if config.auto_gamma
+    {
+        GammaMode::Auto
+    } else
+    {
+        GammaMode::Manual{fullbright: false, current: controls.gamma.current()}
+    };
+
+    let shared_camera = Arc::new(Mutex::new(camera));
+    let capture_running = Arc::new(AtomicBool::new(true));
+
+    let (frame_tx, frame_rx) = mpsc::sync_channel(1);
+    let direct_yuyv = Arc::new(AtomicBool::new(false));
+
+    // frames the main loop was too slow to take, split into rgb and yuyv
+    let skipped_frames = Arc::new([AtomicU64::new(0), AtomicU64::new(0)]);
+
+    let capture_thread = {
+        let shared_camera = shared_camera.clone();
+        let capture_running = capture_running.clone();
+        let direct_yuyv = direct_yuyv.clone();
+        let skipped_frames = skipped_frames.clone();
+
+        thread::spawn(move ||
+        {
+            while capture_running.load(Ordering::Relaxed)
+            {
+                let frame = shared_camera.lock().unwrap().frame();
+
+                let image = frame.map_err(CaptureError::Frame).and_then(|frame|
+                {
+                    if direct_yuyv.load(Ordering::Relaxed) && frame.source_frame_format() == FrameFormat::YUYV
+                    {
+                        Ok(CapturedFrame::Yuyv(frame))
+                    } else
+                    {
+                        frame.decode_image::<RgbFormat>().map(CapturedFrame::Rgb).map_err(CaptureError::Decode)
+                    }
+                });
+
+                let failed = image.is_err();
+
+                match frame_tx.try_send(image)
+                {
+                    Ok(()) => (),
+                    Err(TrySendError::Full(image)) =>
+                    {
+                        let index = matches!(image, Ok(CapturedFrame::Yuyv(_))) as usize;
+
+                        skipped_frames[index].fetch_add(1, Ordering::Relaxed);
+                    },
+                    Err(TrySendError::Disconnected(_)) => return
+                }
+
+                if failed
+                {
+                    thread::sleep(Duration::from_millis(10));
+                }
+            }
+        })
+    };
+
+    let mut averager = Averager::new(args.fps_window.unwrap_or(5));
+
+    let frame_budget = args.max_fps.filter(|fps|
+    {
+        let valid = *fps > 0;
+        if !valid
+        {
+            eprintln!("max fps must be above 0, ignoring");
+        }
+
+        valid
+    }).map(|fps| Duration::from_secs_f64(1.0 / fps as f64));
+    let mut target_brightness = validated_arg(
+        "target brightness",
+        args.target_brightness.or(config.target_brightness),
+        0.0..=100.0,
+        15.0
+    );
+    let brightness_range = validated_arg("brightness range", args.brightness_range, 0.0..=50.0, 10.0);
+
+    let mut center_weighted = false;
+    let mut grid_metering = false;
+
+    let mut software_gamma: Option<GammaLut> = None;
+
+    let brightness_hysteresis = validated_arg("brightness hysteresis", args.brightness_hysteresis, 0.0..=50.0, 5.0);
+    let mut last_correction: Option<i64> = None;
+
+    let mut gamma_pi = PiController::new(0.002, 0.0002, 50.0);
+    let mut exposure_locked = false;
+
+    let gamma_interval = args.gamma_interval.unwrap_or(1).max(1);
+    let metering_stride = args.metering_stride.unwrap_or(1).max(1);
+
+    let auto_gain = args.auto_gain;
+
+    let mut mirrored = args.mirror.unwrap_or(config.mirrored);
+    let mut flipped_vertical = args.flip_vertical.unwrap_or(config.flipped_vertical);
+
+    // values from the command line are only for this run unless toggled
+    let mut save_mirrored = args.mirror.is_none();
+    let mut save_flipped_vertical = args.flip_vertical.is_none();
+    let mut rotation: u16 = initial_rotation.unwrap_or(0);
+
+    let mut snapshot_requested = false;
+    let mut copy_requested = false;
+
+    let mut key_bindings = bindings::load_bindings(&config.keybindings);
+
+    let mut config_modified = Config::modified(config_camera);
+    let mut last_config_check = Instant::now();
+
+    let mut show_help = false;
+
+    let mut show_status = false;
+    let mut status_line = String::new();
+    let mut show_histogram = false;
+    let mut show_rgb_histogram = false;
+    let mut show_waveform = false;
+    let mut false_color = false;
+
+    let zebra_threshold = validated_arg("zebra threshold", args.zebra_threshold, 0.0..=100.0, 95.0);
+    let mut show_zebra = false;
+
+    let mut frame_index: u32 = 0;
+
+    let mut show_counter = false;
+    let mut shown_frames: u64 = 0;
+    let mut counter_start = Instant::now();
+
+    let mut show_crosshair = false;
+    let mut show_grid = false;
+
+    let mut show_timestamp = false;
+    let mut timestamp_corner = Corner::BottomRight;
+    let crosshair_color = {
+        let HexColor([r, g, b]) = args.crosshair_color.unwrap_or(HexColor([255, 0, 0]));
+
+        Color::RGB(r, g, b)
+    };
+    let filter_defaults = &config.filters;
+
+    let mut grayscale = filter_defaults.grayscale;
+    let mut inverted = filter_defaults.invert;
+    let mut sepia = filter_defaults.sepia;
+    let mut sharpened = filter_defaults.sharpen;
+    let mut blurred = filter_defaults.blur;
+    let mut edges = filter_defaults.edges;
+    let mut software_white_balance = filter_defaults.white_balance;
+
+    let mut night: Option<NightMode> = None;
+
+    let calibration_settle = 3;
+    let mut calibration: Option<Calibration> = None;
+
+    let mut auto_levels = filter_defaults.auto_levels;
+    let mut levels: Option<(u8, u8)> = None;
+    let mut levels_delay = 0;
+
+    let vignette_strength = validated_arg("vignette strength", args.vignette_strength, 0.0..=4.0, 0.5);
+    let mut vignette_corrected = filter_defaults.vignette;
+    let mut vignette_map: Option<VignetteMap> = None;
+
+    let distortion = (args.k1.unwrap_or(0.0), args.k2.unwrap_or(0.0));
+    let undistort = distortion != (0.0, 0.0);
+    let mut undistort_map: Option<UndistortMap> = None;
+
+    let mut isolated_channel = Channel::All;
+
+    let posterize_levels = args.posterize_levels.unwrap_or(4).clamp(2, 256);
+
+    let scan_window = args.scan_window.unwrap_or(31).clamp(3, 255);
+    let mut scan_mode = filter_defaults.scan;
+    let mut posterized = filter_defaults.posterize;
+
+    let mut mosaic_block: u32 = 16;
+    let mut mosaic = false;
+    let mut mosaic_area: Option<((f32, f32), (f32, f32))> = None;
+
+    let mut reference_requested = false;
+    let mut reference_image: Option<RgbImage> = None;
+
+    let mut paused = false;
+    let mut paused_image: Option<RgbImage> = None;
+
+    let motion_threshold = validated_arg("motion threshold", args.motion_threshold, 0.0..=100.0, 5.0);
+    let motion_snapshot = args.motion_snapshot;
+    let motion_requested = args.motion_threshold.is_some() || motion_snapshot;
+    let mut previous_plane: Option<((u32, u32), Vec<f32>)> = None;
+    let mut motion_detected = false;
+
+    let still_snapshot = args.still_snapshot;
+    let still_threshold = validated_arg("still threshold", args.still_threshold, 0.0..=100.0, 1.0);
+    let still_time = Duration::from_secs_f64(validated_arg("still time", args.still_time, 0.0..=60.0, 1.5));
+    let mut still_since: Option<Instant> = None;
+    let mut still_captured = false;
+
+    let idle_timeout = args.idle_timeout.filter(|seconds| *seconds > 0.0).map(Duration::from_secs_f64);
+    let mut last_activity = Instant::now();
+
+    let motion_needed = motion_requested || still_snapshot || idle_timeout.is_some();
+
+    let timelapse_interval = args.timelapse.filter(|seconds|
+    {
+        let valid = seconds.is_finite() && *seconds > 0.0;
+        if !valid
+        {
+            eprintln!("timelapse interval must be a positive number, ignoring");
+        }
+
+        valid
+    }).map(Duration::from_secs_f64);
+
+    let timelapse_start = unix_millis();
+    let mut timelapse_index = 0_u32;
+    let mut last_timelapse: Option<Instant> = None;
+
+    let mut recording: Option<Vec<RgbImage>> = None;
+
+    let replay_frames = args.replay.map(|seconds|
+    {
+        (seconds.clamp(0.0, 60.0) * camera_fps as f64).round().max(1.0) as usize
+    }).unwrap_or(0);
+
+    let mut replay: Option<VecDeque<RgbImage>> = args.replay.map(|_| VecDeque::with_capacity(replay_frames));
+
+    let burst_frames = args.burst_frames.unwrap_or(10).max(1);
+    let max_recording_frames = args.max_record_frames.unwrap_or(MAX_RECORDING_FRAMES).max(1);
+    let mut burst: Option<Vec<RgbImage>> = None;
+    let mut encoding_threads = Vec::new();
+
+    let mut title_delay = 0;
+
+    let mut stats_log = args.stats_log.as_ref().and_then(|path|
+    {
+        let file = OpenOptions::new().create(true).append(true).open(path);
+
+        match file
+        {
+            Ok(file) =>
+            {
+                let mut writer = BufWriter::new(file);
+
+                if writer.get_ref().metadata().map(|metadata| metadata.len() == 0).unwrap_or(false)
+                {
+                    let _ = writeln!(writer, "timestamp,fps,gamma,dropped");
+                }
+
+                Some(writer)
+            },
+            Err(err) =>
+            {
+                eprintln!("error opening stats log {path}: {err}");
+
+                None
+            }
+        }
+    });
+
+    let mut last_stats = Instant::now();
+
+    let quality = validated_arg("quality", args.quality, 1.0..=100.0, 80.0).round() as u8;
+
+    let server = args.serve.and_then(|port|
+    {
+        MjpegServer::start(port, quality).map_err(|err|
+        {
+            eprintln!("error starting the stream server on port {port}: {err}");
+        }).ok()
+    });
+
+    let mut scene_temperature: Option<f64> = None;
+
+    let mut roi_start: Option<(f32, f32)> = None;
+    let mut roi_selection: Option<((f32, f32), (f32, f32))> = None;
+    let mut roi: Option<(u32, u32, u32, u32)> = None;
+
+    let mut focused_control = ControlKind::Gamma;
+    let mut ptz_mode = false;
+    let mut value_entry: Option<String> = None;
+
+    let flash_duration = Duration::from_secs(2);
+    let mut flash: Option<(String, Instant)> = None;
+
+    let mut resized = false;
+    let mut last_frame = Instant::now();
+
+    let (tx, rx) = mpsc::channel();
+
+    let borderless = args.borderless;
+    let software_render = args.software_render;
+    let silent = args.silent;
+    let scale_quality = args.scale_quality.unwrap_or(ScaleQuality::Linear);
+    let exact_size = args.no_resize;
+
+    let title_format = args.title_format.clone();
+    let yuyv_decode = args.yuyv_decode;
+    let mut always_on_top = config.always_on_top.unwrap_or(true);
+    let mut scale_mode = args.scale_mode.unwrap_or(ScaleMode::Fit);
+
+    let requested_position = args.position.map(|position| (position.x, position.y))
+        .or(args.display.is_none().then_some(config.window_position).flatten());
+    let requested_display = args.display;
+
+    let slow_events = Arc::new(Mutex::new(Vec::new()));
+    let cursor_position: Arc<Mutex<Option<(f32, f32)>>> = Arc::new(Mutex::new(None));
+    let window_position: Arc<Mutex<Option<(i32, i32)>>> = Arc::new(Mutex::new(None));
+    // keys like minus go to the value entry instead of the crop while its open
+    let entry_open = Arc::new(AtomicBool::new(false));
+
+    let input_thread = {
+        let slow_events = slow_events.clone();
+        let cursor_position = cursor_position.clone();
+        let window_position = window_position.clone();
+        let entry_open = entry_open.clone();
+
+        thread::spawn(move ||
+        {
+            let ctx = sdl2::init().unwrap();
+
+            let video = ctx.video().unwrap();
+            video.enable_screen_saver();
+
+            let beep = (!silent).then(|| ctx.audio().and_then(|audio| open_beep(&audio)))
+                .and_then(|beep| beep.inspect_err(|err| eprintln!("error opening audio: {err}")).ok());
+
+            let mut window_builder = video.window("cam", width, height);
+
+            if !exact_size
+            {
+                window_builder.resizable();
+            }
+
+            if always_on_top
+            {
+                window_builder.always_on_top();
+            }
+
+            if borderless
+            {
+                window_builder.borderless();
+            }
+
+            match startup_position(&video, requested_position, requested_display, (width, height))
+            {
+                Some((x, y)) => window_builder.position(x, y),
+                None => window_builder.position_centered()
+            };
+
+            let window = window_builder.build().unwrap();
+
+            *window_position.lock().unwrap() = Some(window.position());
+
+            if !sdl2::hint::set("SDL_RENDER_SCALE_QUALITY", scale_quality.hint_value())
+            {
+                eprintln!("couldnt set the scale quality to {}", scale_quality.hint_value());
+            }
+
+            let canvas_builder = window.into_canvas();
+
+            let canvas_builder = if software_render
+            {
+                canvas_builder.software()
+            } else
+            {
+                canvas_builder.accelerated()
+            };
+
+            let mut canvas = canvas_builder.build().unwrap();
+
+            canvas.clear();
+            canvas.present();
+
+            let mut events = ctx.event_pump().unwrap();
+
+            let texture_creator = canvas.texture_creator();
+            let mut camera_texture: Option<Texture> = None;
+            let mut texture_format = PixelFormatEnum::RGB24;
+            let mut frame_size = (width, height);
+            let mut windowed_size = (width, height);
+
+            let mut drag_origin: Option<(i32, i32)> = None;
+            let mut last_image_area: Option<Rect> = None;
+            let mut last_source_area: Option<Rect> = None;
+            let mut scale_mode = scale_mode;
+
+            let mut crop_info = CropInfo::new();
+            let mut crop_controls = [false; CropControl::Length as usize];
+
+            fn crop_control_of(
+                code: Keycode,
+                keymod: Mod,
+                ignore_shift: bool,
+                mut f: impl FnMut(CropControl) -> bool
+            ) -> bool
+            {
+                let is_shift = keymod == Mod::LSHIFTMOD;
+                let is_alt = keymod.intersects(Mod::LALTMOD | Mod::RALTMOD);
+
+                match code
+                {
                     Keycode::Minus =>
                     {
-                        if ignore_shift
-                        {
-                            f(CropControl::ZoomXMinus);
-                            f(CropControl::ZoomYMinus)
-                        } else if is_shift
-                        {
-                            f(CropControl::ZoomYMinus)
-                        } else
+                        if ignore_shift
+                        {
+                            f(CropControl::ZoomXMinus);
+                            f(CropControl::ZoomYMinus)
+                        } else if is_shift
+                        {
+                            f(CropControl::ZoomYMinus)
+                        } else
+                        {
+                            f(CropControl::ZoomXMinus)
+                        }
+                    },
+                    Keycode::Equals =>
+                    {
+                        if ignore_shift
+                        {
+                            f(CropControl::ZoomXPlus);
+                            f(CropControl::ZoomYPlus)
+                        } else if is_shift
+                        {
+                            f(CropControl::ZoomYPlus)
+                        } else
+                        {
+                            f(CropControl::ZoomXPlus)
+                        }
+                    },
+                    Keycode::Up if is_alt || ignore_shift => f(CropControl::ShiftYPlus),
+                    Keycode::Down if is_alt || ignore_shift => f(CropControl::ShiftYMinus),
+                    Keycode::Right if is_alt || ignore_shift => f(CropControl::ShiftXPlus),
+                    Keycode::Left if is_alt || ignore_shift => f(CropControl::ShiftXMinus),
+                    _ => false
+                }
+            }
+
+            loop
+            {
+                let received: Option<ProgramMessage> = match rx.try_recv()
+                {
+                    Ok(x) => Some(x),
+                    Err(TryRecvError::Empty) => None,
+                    _ => return
+                };
+
+                let low_zoom = 0.01;
+                let mut mouse_zoomed = false;
+
+                for event in events.poll_iter()
+                {
+                    match event
+                    {
+                        Event::MouseMotion{x, y, ..} =>
+                        {
+                            let (window_width, window_height) = canvas.window().size();
+                            let (output_width, output_height) = canvas.output_size().unwrap();
+
+                            let x = x as f32 * output_width as f32 / window_width.max(1) as f32;
+                            let y = y as f32 * output_height as f32 / window_height.max(1) as f32;
+
+                            let areas = last_image_area.zip(last_source_area);
+                            *cursor_position.lock().unwrap() = areas.and_then(|(area, source): (Rect, Rect)|
+                            {
+                                let u = (x - area.x() as f32) / area.width() as f32;
+                                let v = (y - area.y() as f32) / area.height() as f32;
+
+                                let inside = (0.0..1.0).contains(&u) && (0.0..1.0).contains(&v);
+
+                                inside.then_some((
+                                    (source.x() as f32 + u * source.width() as f32) / frame_size.0 as f32,
+                                    (source.y() as f32 + v * source.height() as f32) / frame_size.1 as f32
+                                ))
+                            });
+                        },
+                        Event::Window{win_event: WindowEvent::Leave, ..} =>
+                        {
+                            *cursor_position.lock().unwrap() = None;
+                        },
+                        Event::Window{win_event: WindowEvent::Moved(x, y), ..}
+                            if canvas.window().fullscreen_state() == FullscreenType::Off =>
+                        {
+                            *window_position.lock().unwrap() = Some((x, y));
+                        },
+                        _ => ()
+                    }
+
+                    match event
+                    {
+                        Event::KeyUp{keycode: Some(code), keymod, ..} =>
+                        {
+                            let handled = !exact_size && crop_control_of(code, keymod, true, |c|
+                            {
+                                crop_controls[c as usize] = false;
+
+                                true
+                            });
+
+                            if handled
+                            {
+                                continue;
+                            }
+                        },
+                        Event::KeyDown{keycode: Some(code), keymod, ..} =>
+                        {
+                            let crop_keys = !exact_size && !entry_open.load(Ordering::Relaxed);
+
+                            let handled = crop_keys && crop_control_of(code, keymod, false, |c|
+                            {
+                                crop_controls[c as usize] = true;
+
+                                true
+                            });
+
+                            if handled
+                            {
+                                continue;
+                            }
+                        },
+                        Event::MouseWheel{y, ..} if !exact_size =>
+                        {
+                            crop_info.zoom(0.9_f32.powi(y), low_zoom);
+                            mouse_zoomed = true;
+
+                            continue;
+                        },
+                        Event::MouseButtonDown{mouse_btn: MouseButton::Right, x, y, ..} if borderless =>
+                        {
+                            drag_origin = Some((x, y));
+
+                            continue;
+                        },
+                        Event::MouseButtonUp{mouse_btn: MouseButton::Right, ..} if borderless =>
+                        {
+                            drag_origin = None;
+
+                            continue;
+                        },
+                        Event::MouseMotion{mousestate, x, y, ..} if borderless && mousestate.right() =>
+                        {
+                            if let Some((origin_x, origin_y)) = drag_origin
+                            {
+                                let window = canvas.window_mut();
+                                let (window_x, window_y) = window.position();
+
+                                window.set_position(
+                                    WindowPos::Positioned(window_x + x - origin_x),
+                                    WindowPos::Positioned(window_y + y - origin_y)
+                                );
+                            }
+
+                            continue;
+                        },
+                        Event::MouseMotion{mousestate, xrel, yrel, ..} if !exact_size && mousestate.left() =>
+                        {
+                            let (window_width, window_height) = canvas.window().size();
+
+                            crop_info.pan(
+                                -(xrel as f32 / window_width as f32) * crop_info.scale_x,
+                                -(yrel as f32 / window_height as f32) * crop_info.scale_y
+                            );
+
+                            continue;
+                        },
+                        _ => ()
+                    }
+
+                    slow_events.lock().unwrap().push(event);
+                }
+
+                let update_aspect = |
+                    canvas: &mut WindowCanvas,
+                    crop_info: CropInfo,
+                    (width, height): (u32, u32),
+                    scale_mode: ScaleMode
+                |
+                {
+                    if exact_size
+                    {
+                        let window = canvas.window_mut();
+
+                        if window.fullscreen_state() != FullscreenType::Off || window.size() == (width, height)
+                        {
+                            return false;
+                        }
+
+                        if let Err(err) = window.set_size(width, height)
+                        {
+                            eprintln!("window resize error: {err}");
+                        }
+
+                        return true;
+                    }
+
+                    if scale_mode != ScaleMode::Fit
+                    {
+                        return false;
+                    }
+
+                    let aspect = (width as f64 * crop_info.scale_x as f64)
+                        / (height as f64 * crop_info.scale_y as f64);
+
+                    set_closest_aspect(canvas, aspect)
+                };
+
+                let dt: f32 = 1.0 / update_fps as f32;
+
+                let zoom_speed = 0.25;
+
+                let zoom_in_factor = 1.0 - dt * zoom_speed;
+                let zoom_out_factor = 1.0 + dt * zoom_speed;
+
+                let c = |x| crop_controls[x as usize];
+
+                if c(CropControl::ZoomXPlus)
+                {
+                    crop_info.scale_x = (crop_info.scale_x * zoom_in_factor).clamp(low_zoom, 1.0);
+                }
+
+                if c(CropControl::ZoomYPlus)
+                {
+                    crop_info.scale_y = (crop_info.scale_y * zoom_in_factor).clamp(low_zoom, 1.0);
+                }
+
+                if c(CropControl::ZoomXMinus)
+                {
+                    crop_info.scale_x = (crop_info.scale_x * zoom_out_factor).clamp(low_zoom, 1.0);
+                }
+
+                if c(CropControl::ZoomYMinus)
+                {
+                    crop_info.scale_y = (crop_info.scale_y * zoom_out_factor).clamp(low_zoom, 1.0);
+                }
+
+                let change_pos = |value: &mut f32, amount: f32, zoom: f32|
+                {
+                    let half_zoom = zoom * 0.5;
+
+                    let low = half_zoom;
+                    let high = 1.0 - half_zoom;
+
+                    *value = (*value + amount * zoom).clamp(low, high);
+                };
+
+                let move_speed = 0.8 * dt;
+
+                if c(CropControl::ShiftXPlus)
+                {
+                    change_pos(&mut crop_info.pos_x, move_speed, crop_info.scale_x);
+                }
+
+                if c(CropControl::ShiftXMinus)
+                {
+                    change_pos(&mut crop_info.pos_x, -move_speed, crop_info.scale_x);
+                }
+
+                if c(CropControl::ShiftYMinus)
+                {
+                    change_pos(&mut crop_info.pos_y, move_speed, crop_info.scale_y);
+                }
+
+                if c(CropControl::ShiftYPlus)
+                {
+                    change_pos(&mut crop_info.pos_y, -move_speed, crop_info.scale_y);
+                }
+
+                if mouse_zoomed
+                    || c(CropControl::ZoomXPlus)
+                    || c(CropControl::ZoomXMinus)
+                    || c(CropControl::ZoomYPlus)
+                    || c(CropControl::ZoomYMinus)
+                {
+                    update_aspect(&mut canvas, crop_info, frame_size, scale_mode);
+                }
+
+                if let Some(received) = received
+                {
+                    match received
+                    {
+                        ProgramMessage::Render(frame, overlays) =>
+                        {
+                            let (original_width, original_height) = frame.size();
+                            let pixel_format = frame.pixel_format();
+                            let pitch = frame.pitch();
+
+                            let mut data = frame.into_raw();
+
+                            let size_changed = frame_size != (original_width, original_height);
+                            let format_changed = texture_format != pixel_format;
+
+                            if camera_texture.is_none() || size_changed || format_changed
+                            {
+                                frame_size = (original_width, original_height);
+                                texture_format = pixel_format;
+
+                                camera_texture = Some(texture_creator.create_texture_streaming(
+                                    pixel_format,
+                                    original_width,
+                                    original_height
+                                ).unwrap());
+
+                                if size_changed
+                                {
+                                    update_aspect(&mut canvas, crop_info, frame_size, scale_mode);
+                                }
+                            }
+
+                            {
+                                let camera_texture = camera_texture.as_mut().unwrap();
+
+                                camera_texture.update(
+                                    None,
+                                    &mut data,
+                                    pitch
+                                ).unwrap();
+                            }
+
+                            let camera_texture = camera_texture.as_ref().unwrap();
+
+                            let width = (original_width as f32 * crop_info.scale_x) as u32;
+                            let height = (original_height as f32 * crop_info.scale_y) as u32;
+
+                            let pos_of = |p: f32, s: f32, original: u32| -> i32
+                            {
+                                ((p - s * 0.5) * original as f32) as i32
+                            };
+
+                            let cropped_rect = Rect::new(
+                                pos_of(crop_info.pos_x, crop_info.scale_x, original_width),
+                                pos_of(crop_info.pos_y, crop_info.scale_y, original_height),
+                                width,
+                                height
+                            );
+
+                            let (output_width, output_height) = canvas.output_size().unwrap();
+
+                            let fullscreen = canvas.window().fullscreen_state() != FullscreenType::Off;
+                            let (source, destination) = match scale_mode
+                            {
+                                ScaleMode::Fit if fullscreen =>
+                                {
+                                    canvas.clear();
+
+                                    let aspect = width as f64 / height as f64;
+                                    (cropped_rect, Some(letterbox_rect((output_width, output_height), aspect)))
+                                },
+                                ScaleMode::Fill =>
+                                {
+                                    let aspect = output_width as f64 / output_height as f64;
+                                    let filled = letterbox_rect((width, height), aspect);
+
+                                    let source = Rect::new(
+                                        cropped_rect.x() + filled.x(),
+                                        cropped_rect.y() + filled.y(),
+                                        filled.width(),
+                                        filled.height()
+                                    );
+
+                                    (source, None)
+                                },
+                                _ => (cropped_rect, None)
+                            };
+
+                            canvas.copy(camera_texture, Some(source), destination).unwrap();
+
+                            let image_area = destination.unwrap_or_else(||
+                            {
+                                Rect::new(0, 0, output_width, output_height)
+                            });
+
+                            last_image_area = Some(image_area);
+                            last_source_area = Some(source);
+
+                            if overlays.grid
+                            {
+                                draw_grid(&mut canvas, image_area);
+                            }
+
+                            if let Some(color) = overlays.crosshair
+                            {
+                                draw_crosshair(&mut canvas, color, image_area);
+                            }
+
+                            if let Some(histogram) = overlays.histogram
+                            {
+                                draw_histogram(&mut canvas, &histogram, image_area);
+                            }
+
+                            if let Some(histograms) = overlays.rgb_histogram
+                            {
+                                draw_rgb_histogram(&mut canvas, &histograms, image_area);
+                            }
+
+                            if let Some(waveform) = overlays.waveform
+                            {
+                                draw_waveform(&mut canvas, &waveform, image_area);
+                            }
+
+                            if let Some(status) = overlays.status
+                            {
+                                draw_status(&mut canvas, &status, image_area);
+                            }
+
+                            if let Some(help) = overlays.help
+                            {
+                                draw_help(&mut canvas, &help, image_area);
+                            }
+                            canvas.present();
+                        },
+                        ProgramMessage::ResetWindow =>
+                        {
+                            if let Err(err) = canvas.window_mut().set_size(frame_size.0, frame_size.1)
+                            {
+                                eprintln!("error setting window size: {err}");
+                            }
+
+                            windowed_size = frame_size;
+                            crop_info = CropInfo::new();
+                        },
+                        ProgramMessage::ResizeWindow(width, height) =>
+                        {
+                            if canvas.window().fullscreen_state() == FullscreenType::Off
+                            {
+                                if let Err(err) = canvas.window_mut().set_size(width, height)
+                                {
+                                    eprintln!("error setting window size: {err}");
+                                }
+                            }
+
+                            windowed_size = (width, height);
+                            crop_info = CropInfo::new();
+                        },
+                        ProgramMessage::ToggleFullscreen =>
+                        {
+                            let window = canvas.window_mut();
+
+                            if window.fullscreen_state() == FullscreenType::Off
+                            {
+                                windowed_size = window.size();
+
+                                if let Err(err) = window.set_fullscreen(FullscreenType::Desktop)
+                                {
+                                    eprintln!("error entering fullscreen: {err}");
+                                }
+                            } else
+                            {
+                                if let Err(err) = window.set_fullscreen(FullscreenType::Off)
+                                {
+                                    eprintln!("error leaving fullscreen: {err}");
+                                }
+
+                                if let Err(err) = window.set_size(windowed_size.0, windowed_size.1)
+                                {
+                                    eprintln!("error setting window size: {err}");
+                                }
+
+                                update_aspect(&mut canvas, crop_info, frame_size, scale_mode);
+                            }
+                        },
+                        ProgramMessage::SetAlwaysOnTop(on_top) =>
+                        {
+                            canvas.window_mut().set_always_on_top(on_top);
+                        },
+                        ProgramMessage::SetScaleMode(mode) =>
+                        {
+                            scale_mode = mode;
+
+                            update_aspect(&mut canvas, crop_info, frame_size, scale_mode);
+                        },
+                        ProgramMessage::Beep =>
+                        {
+                            if let Some((queue, samples)) = beep.as_ref()
+                            {
+                                queue.clear();
+
+                                if let Err(err) = queue.queue_audio(samples)
+                                {
+                                    eprintln!("error playing beep: {err}");
+                                }
+
+                                queue.resume();
+                            }
+                        },
+                        ProgramMessage::SetClosestAspect =>
+                        {
+                            update_aspect(&mut canvas, crop_info, frame_size, scale_mode);
+                        },
+                        ProgramMessage::SetTitle(title) =>
+                        {
+                            if let Err(err) = canvas.window_mut().set_title(&title)
+                            {
+                                eprintln!("error updating title: {err}");
+                            }
+                        }
+                    }
+                }
+
+                thread::sleep(Duration::from_millis(1000 / update_fps as u64));
+            }
+        })
+    };
+
+    'window_loop: loop
+    {
+        let mut camera = CameraLock::new(&shared_camera);
+        for event in slow_events.lock().unwrap().drain(..)
+        {
+            if matches!(event, Event::KeyDown{..} | Event::MouseButtonDown{..})
+            {
+                last_activity = Instant::now();
+            }
+
+            match event
+            {
+                Event::Quit{..} => break 'window_loop,
+                Event::MouseButtonDown{mouse_btn: MouseButton::Middle, ..} =>
+                {
+                    roi_start = *cursor_position.lock().unwrap();
+                },
+                Event::MouseButtonUp{mouse_btn: MouseButton::Middle, ..} =>
+                {
+                    let end = *cursor_position.lock().unwrap();
+
+                    if let (Some(start), Some(end)) = (roi_start.take(), end)
+                    {
+                        if mosaic
+                        {
+                            mosaic_area = Some((start, end));
+                        } else
+                        {
+                            roi_selection = Some((start, end));
+                        }
+                    }
+                },
+                Event::Window{win_event: WindowEvent::Resized(_, _), ..} if !exact_size =>
+                {
+                    resized = true;
+                },
+                Event::KeyDown{keycode: Some(code), keymod, ..} if value_entry.is_some() =>
+                {
+                    let entry = value_entry.as_mut().unwrap();
+
+                    let name = code.name();
+                    let typed = name.strip_prefix("Keypad ").unwrap_or(&name);
+
+                    match code
+                    {
+                        Keycode::Return | Keycode::KpEnter =>
+                        {
+                            match entry.parse::<f64>()
+                            {
+                                Ok(value) =>
+                                {
+                                    if let (ControlKind::Gamma, Some(gamma_lut)) = (focused_control, software_gamma.as_mut())
+                                    {
+                                        *gamma_lut = GammaLut::new(value.clamp(0.1, 5.0));
+                                    } else
+                                    {
+                                        controls.get_mut(focused_control).set(&mut camera, value.round() as i64);
+
+                                        if let (ControlKind::Gamma, GammaMode::Manual{ref mut current, ..}) = (focused_control, &mut gamma_mode)
+                                        {
+                                            *current = controls.gamma.current();
+                                        }
+                                    }
+                                },
+                                Err(_) if entry.is_empty() => (),
+                                Err(err) => eprintln!("invalid value {entry}: {err}")
+                            }
+
+                            value_entry = None;
+                        },
+                        Keycode::Escape => value_entry = None,
+                        Keycode::Backspace =>
+                        {
+                            entry.pop();
+                        },
+                        Keycode::Minus | Keycode::KpMinus if entry.is_empty() => entry.push('-'),
+                        _ if keymod.intersects(Mod::LSHIFTMOD | Mod::RSHIFTMOD) => (),
+                        _ if typed.len() == 1 && typed.chars().all(|c| c.is_ascii_digit() || c == '.') =>
+                        {
+                            entry.push_str(typed);
+                        },
+                        _ => ()
+                    }
+
+                    title_delay = 0;
+                },
+                Event::KeyDown{keycode: Some(code), keymod, ..} if ptz_mode
+                    && !keymod.intersects(Mod::LALTMOD | Mod::RALTMOD)
+                    && matches!(code, Keycode::Up | Keycode::Down | Keycode::Left | Keycode::Right) =>
+                {
+                    let step = if keymod.intersects(Mod::LSHIFTMOD | Mod::RSHIFTMOD) { 10 } else { 1 };
+
+                    let (kind, direction) = match code
+                    {
+                        Keycode::Up => (ControlKind::Tilt, step),
+                        Keycode::Down => (ControlKind::Tilt, -step),
+                        Keycode::Right => (ControlKind::Pan, step),
+                        _ => (ControlKind::Pan, -step)
+                    };
+
+                    focused_control = kind;
+                    controls.get_mut(kind).step_by(&mut camera, direction);
+
+                    title_delay = 0;
+                },
+                Event::KeyDown{keycode: Some(code @ (Keycode::Up | Keycode::Down)), keymod, ..}
+                    if keymod.intersects(Mod::LCTRLMOD | Mod::RCTRLMOD) =>
+                {
+                    let step = if keymod.intersects(Mod::LSHIFTMOD | Mod::RSHIFTMOD) { 10 } else { 1 };
+                    let direction = if code == Keycode::Up { step } else { -step };
+
+                    focused_control = ControlKind::Gain;
+                    controls.gain.step_by(&mut camera, direction);
+
+                    title_delay = 0;
+                },
+                Event::KeyDown{keycode: Some(code), keymod, ..} =>
+                {
+                    let Some(&action) = key_bindings.get(&code) else
+                    {
+                        continue;
+                    };
+
+                    let step = if keymod.intersects(Mod::LSHIFTMOD | Mod::RSHIFTMOD)
+                    {
+                        10
+                    } else
+                    {
+                        1
+                    };
+
+                    let direction = match action
+                    {
+                        Action::GammaUp
+                            | Action::ContrastUp
+                            | Action::SaturationUp
+                            | Action::ExposureUp
+                            | Action::WhiteBalanceUp
+                            | Action::FocusIn
+                            | Action::ZoomIn => step,
+                        _ => -step
+                    };
+
+                    match action
+                    {
+                        Action::Help =>
+                        {
+                            show_help = !show_help;
+                        },
+                        Action::Quit => break 'window_loop,
+                        Action::ResetWindow =>
+                        {
+                            tx.send(ProgramMessage::ResetWindow).unwrap();
+                        },
+                        Action::Fullscreen =>
+                        {
+                            tx.send(ProgramMessage::ToggleFullscreen).unwrap();
+                        },
+                        Action::AlwaysOnTop =>
+                        {
+                            always_on_top = !always_on_top;
+
+                            tx.send(ProgramMessage::SetAlwaysOnTop(always_on_top)).unwrap();
+                        },
+                        Action::Mirror =>
+                        {
+                            mirrored = !mirrored;
+                            save_mirrored = true;
+                        },
+                        Action::FlipVertical =>
+                        {
+                            flipped_vertical = !flipped_vertical;
+                            save_flipped_vertical = true;
+                        },
+                        Action::Rotate =>
+                        {
+                            rotation = (rotation + 90) % 360;
+                        },
+                        Action::Snapshot =>
+                        {
+                            snapshot_requested = true;
+                        },
+                        Action::CopySnapshot =>
+                        {
+                            copy_requested = true;
+                        },
+                        Action::Histogram =>
+                        {
+                            show_histogram = !show_histogram;
+                        },
+                        Action::RgbHistogram =>
+                        {
+                            show_rgb_histogram = !show_rgb_histogram;
+                        },
+                        Action::Waveform =>
+                        {
+                            show_waveform = !show_waveform;
+                        },
+                        Action::ClearRegion =>
+                        {
+                            roi = None;
+
+                            mosaic_area = None;
+                        },
+                        Action::FalseColor =>
+                        {
+                            false_color = !false_color;
+                        },
+                        Action::Zebra =>
+                        {
+                            show_zebra = !show_zebra;
+                        },
+                        Action::Grayscale =>
+                        {
+                            grayscale = !grayscale;
+                            sepia = false;
+                        },
+                        Action::Invert =>
+                        {
+                            inverted = !inverted;
+                            sepia = false;
+                        },
+                        Action::Sepia =>
+                        {
+                            sepia = !sepia;
+
+                            if sepia
+                            {
+                                grayscale = false;
+                                inverted = false;
+                            }
+                        },
+                        Action::Sharpen =>
+                        {
+                            sharpened = !sharpened;
+                        },
+                        Action::Blur =>
+                        {
+                            blurred = !blurred;
+                        },
+                        Action::Timestamp =>
+                        {
+                            show_timestamp = !show_timestamp;
+                        },
+                        Action::TimestampCorner =>
+                        {
+                            timestamp_corner = timestamp_corner.next();
+                        },
+                        Action::Grid =>
+                        {
+                            show_grid = !show_grid;
+                        },
+                        Action::Crosshair =>
+                        {
+                            show_crosshair = !show_crosshair;
+                        },
+                        Action::Edges =>
+                        {
+                            edges = !edges;
+                        },
+                        Action::SoftwareWhiteBalance =>
+                        {
+                            software_white_balance = !software_white_balance;
+                        },
+                        Action::AutoLevels =>
+                        {
+                            auto_levels = !auto_levels;
+                            levels = None;
+                        },
+                        Action::Vignette =>
+                        {
+                            vignette_corrected = !vignette_corrected;
+                        },
+                        Action::Scan =>
+                        {
+                            scan_mode = !scan_mode;
+                        },
+                        Action::Mosaic =>
+                        {
+                            mosaic = !mosaic;
+                            mosaic_area = None;
+                        },
+                        Action::MosaicSize =>
+                        {
+                            mosaic_block = if mosaic_block >= 64 { 4 } else { mosaic_block * 2 };
+                        },
+                        Action::Posterize =>
+                        {
+                            posterized = !posterized;
+                        },
+                        Action::NextResolution =>
+                        {
+                            let Some(resolution) = next_resolution(&mut camera) else
+                            {
+                                flash = Some(("NO OTHER RESOLUTION".to_owned(), Instant::now()));
+                                continue;
+                            };
+
+                            resolution_request = Some((resolution, false));
+                        },
+                        Action::IsolateChannel =>
+                        {
+                            isolated_channel = isolated_channel.next();
+                        },
+                        Action::EnterValue =>
+                        {
+                            value_entry = Some(String::new());
+                        },
+                        Action::Compare =>
+                        {
+                            if reference_image.is_some()
+                            {
+                                reference_image = None;
+                            } else
+                            {
+                                reference_requested = true;
+                            }
+                        },
+                        Action::GridMetering =>
+                        {
+                            grid_metering = !grid_metering;
+                            gamma_pi.reset();
+                        },
+                        Action::CenterWeighted =>
+                        {
+                            center_weighted = !center_weighted;
+                        },
+                        Action::Pause =>
+                        {
+                            paused = !paused;
+                            paused_image = None;
+                        },
+                        Action::Burst =>
+                        {
+                            if burst.is_none()
+                            {
+                                burst = Some(Vec::with_capacity(burst_frames));
+                            }
+                        },
+                        Action::Replay =>
+                        {
+                            if let Some(frames) = replay.as_ref()
+                            {
+                                if !frames.is_empty()
+                                {
+                                    let frames = frames.iter().cloned().collect();
+                                    encoding_threads.push(save_recording("replay", frames, averager.average(), quality));
+                                }
+                            } else
+                            {
+                                flash = Some(("REPLAY NEEDS --replay".to_owned(), Instant::now()));
+                            }
+                        },
+                        Action::Record =>
+                        {
+                            if let Some(frames) = recording.take()
+                            {
+                                if !frames.is_empty()
+                                {
+                                    encoding_threads.push(save_recording("recording", frames, averager.average(), quality));
+                                }
+                            } else
+                            {
+                                recording = Some(Vec::new());
+                            }
+                        },
+                        Action::NextCamera =>
+                        {
+                            let position = camera_indices.iter().position(|&x| x == camera_index).unwrap_or(0);
+
+                            let mut unavailable = Vec::new();
+                            let next = (1..camera_indices.len()).find_map(|offset|
+                            {
+                                let index = camera_indices[(position + offset) % camera_indices.len()];
+
+                                match open_camera(index, camera_format, requested_fps)
+                                {
+                                    Ok(x) => Some((index, x)),
+                                    Err(err) =>
+                                    {
+                                        eprintln!("couldnt open camera {index}: {err}");
+                                        unavailable.push(index.to_string());
+
+                                        None
+                                    }
+                                }
+                            });
+
+                            let next_camera_found = next.is_some();
+                            if let Some((index, next_camera)) = next
+                            {
+                                controls.reset(&mut camera);
+
+                                camera_index = index;
+                                *camera = next_camera;
+
+                                format_text = format_description(&camera);
+                                if verbose
+                                {
+                                    println!("switched to camera {index}, format: {}", camera.camera_format());
+                                }
+
+                                controls = Controls::new(&camera);
+                                gamma_pi.reset();
+                                last_correction = None;
+                                gamma_mode.camera_changed(controls.gamma.current());
+
+                                paused_image = None;
+                            }
+
+                            camera_error = if next_camera_found
+                            {
+                                (!unavailable.is_empty()).then(||
+                                {
+                                    format!("CAMERA {} UNAVAILABLE", unavailable.join(", "))
+                                })
+                            } else
+                            {
+                                Some("NO OTHER CAMERA".to_owned())
+                            };
+                        },
+                        Action::StatusBar =>
+                        {
+                            show_status = !show_status;
+                        },
+                        Action::ResetDropped =>
+                        {
+                            dropped_frames = 0;
+                            dropped_yuyv = 0;
+                        },
+                        Action::FrameCounter =>
+                        {
+                            show_counter = !show_counter;
+                        },
+                        Action::ResetCounter =>
+                        {
+                            shown_frames = 0;
+                            counter_start = Instant::now();
+                        },
+                        Action::ToggleGammaMode =>
+                        {
+                            controls.gamma.reset(&mut camera);
+                            controls.brightness.reset(&mut camera);
+                            controls.exposure.reset(&mut camera);
+                            gamma_pi.reset();
+                            last_correction = None;
+                            exposure_locked = false;
+
+                            gamma_mode = match gamma_mode
+                            {
+                                GammaMode::Manual{..} => GammaMode::Auto,
+                                GammaMode::Auto => GammaMode::Manual{fullbright: false, current: controls.gamma.current()}
+                            };
+                        },
+                        Action::ExposureLock =>
+                        {
+                            if gamma_mode == GammaMode::Auto
+                            {
+                                exposure_locked = !exposure_locked;
+
+                                gamma_pi.reset();
+                                last_correction = None;
+                            }
+                        },
+                        Action::Fullbright =>
+                        {
+                            if let GammaMode::Manual{ref mut fullbright, current} = gamma_mode
+                            {
+                                *fullbright = !*fullbright;
+
+                                if *fullbright
+                                {
+                                    controls.gamma.set_max(&mut camera);
+                                    controls.brightness.set_max(&mut camera);
+                                } else
+                                {
+                                    controls.gamma.set(&mut camera, current);
+                                    controls.brightness.reset(&mut camera);
+                                }
+                            }
+                        },
+                        Action::ScaleMode =>
+                        {
+                            scale_mode = scale_mode.next();
+                            tx.send(ProgramMessage::SetScaleMode(scale_mode)).unwrap();
+                        },
+                        Action::Calibrate =>
+                        {
+                            if let Some(previous) = calibration.take()
+                            {
+                                controls.gamma.set(&mut camera, previous.previous);
+                                gamma_mode = GammaMode::Manual{fullbright: false, current: previous.previous};
+
+                                continue;
+                            }
+
+                            if !controls.gamma.is_supported()
+                            {
+                                flash = Some(("NO GAMMA CONTROL".to_owned(), Instant::now()));
+                                continue;
+                            }
+
+                            let (min, max) = controls.gamma.limits();
+                            let steps = (max - min).clamp(1, 16);
+
+                            let mut values: Vec<i64> = (0..=steps).map(|index| min + (max - min) * index / steps).collect();
+                            values.dedup();
+
+                            controls.gamma.set(&mut camera, values[0]);
+
+                            calibration = Some(Calibration{
+                                previous: match gamma_mode
+                                {
+                                    GammaMode::Manual{current, ..} => current,
+                                    GammaMode::Auto => controls.gamma.current()
+                                },
+                                values,
+                                index: 0,
+                                settle: calibration_settle,
+                                best: None
+                            });
+
+                            gamma_mode = GammaMode::Manual{fullbright: false, current: controls.gamma.current()};
+                            gamma_pi.reset();
+                            last_correction = None;
+                        },
+                        Action::NightMode =>
+                        {
+                            if let Some(previous) = night.take()
+                            {
+                                controls.gamma.set(&mut camera, previous.gamma);
+                                controls.brightness.set(&mut camera, previous.brightness);
+
+                                gamma_mode = previous.gamma_mode;
+                                blurred = previous.blurred;
+                            } else
+                            {
+                                let current = controls.gamma.current();
+
+                                night = Some(NightMode{
+                                    gamma_mode: mem::replace(&mut gamma_mode, GammaMode::Manual{fullbright: false, current}),
+                                    gamma: current,
+                                    brightness: controls.brightness.current(),
+                                    blurred,
+                                    boost: GammaLut::new(1.8)
+                                });
+
+                                controls.gamma.set_max(&mut camera);
+                                controls.brightness.set_max(&mut camera);
+
+                                gamma_pi.reset();
+                                last_correction = None;
+
+                                blurred = true;
+                            }
+                        },
+                        Action::SoftwareGamma =>
+                        {
+                            software_gamma = if software_gamma.is_some()
+                            {
+                                None
+                            } else
+                            {
+                                Some(GammaLut::new(1.0))
+                            };
+                        },
+                        Action::GammaUp | Action::GammaDown =>
+                        {
+                            focused_control = ControlKind::Gamma;
+
+                            if let Some(gamma_lut) = software_gamma.as_mut()
+                            {
+                                let gamma = (gamma_lut.gamma() + direction as f64 * 0.05).clamp(0.1, 5.0);
+
+                                *gamma_lut = GammaLut::new(gamma);
+                            } else if let GammaMode::Manual{ref mut current, ..} = gamma_mode
+                            {
+                                controls.gamma.set(&mut camera, *current + controls.gamma.step() * direction);
+                                *current = controls.gamma.current();
+                            }
+                        },
+                        Action::ContrastUp | Action::ContrastDown =>
+                        {
+                            focused_control = ControlKind::Contrast;
+                            controls.contrast.step_by(&mut camera, direction);
+                        },
+                        Action::SaturationUp | Action::SaturationDown =>
+                        {
+                            focused_control = ControlKind::Saturation;
+                            controls.saturation.step_by(&mut camera, direction);
+                        },
+                        Action::ExposureUp | Action::ExposureDown =>
+                        {
+                            if let GammaMode::Manual{..} = gamma_mode
+                            {
+                                focused_control = ControlKind::Exposure;
+                                controls.exposure.step_by(&mut camera, direction);
+                            }
+                        },
+                        Action::WhiteBalanceUp | Action::WhiteBalanceDown =>
+                        {
+                            focused_control = ControlKind::WhiteBalance;
+                            controls.white_balance.step_by(&mut camera, direction);
+                        },
+                        Action::FocusIn | Action::FocusOut =>
+                        {
+                            focused_control = ControlKind::Focus;
+
+                            controls.auto_focus.set(&mut camera, false);
+                            controls.focus.step_by(&mut camera, direction);
+                        },
+                        Action::PanTilt =>
+                        {
+                            ptz_mode = !ptz_mode;
+                        },
+                        Action::ZoomIn | Action::ZoomOut =>
+                        {
+                            focused_control = ControlKind::Zoom;
+                            controls.zoom.step_by(&mut camera, direction);
+                        },
+                        Action::AutoFocus =>
+                        {
+                            let auto_focus = !controls.auto_focus.current();
+                            controls.auto_focus.set(&mut camera, auto_focus);
+                        },
+                        Action::ResetGamma =>
+                        {
+                            if let Some(gamma_lut) = software_gamma.as_mut()
+                            {
+                                *gamma_lut = GammaLut::new(1.0);
+                            }
+
+                            focused_control = ControlKind::Gamma;
+                            controls.gamma.reset(&mut camera);
+                            gamma_pi.reset();
+
+                            if let GammaMode::Manual{ref mut current, ..} = gamma_mode
+                            {
+                                *current = controls.gamma.current();
+                            }
+
+                            flash = Some(("GAMMA RESET".to_owned(), Instant::now()));
+                        },
+                        Action::ResetBrightness =>
+                        {
+                            focused_control = ControlKind::Brightness;
+                            controls.brightness.reset(&mut camera);
+                            flash = Some(("BRIGHTNESS RESET".to_owned(), Instant::now()));
+                        },
+                        Action::ResetContrast =>
+                        {
+                            focused_control = ControlKind::Contrast;
+                            controls.contrast.reset(&mut camera);
+                            flash = Some(("CONTRAST RESET".to_owned(), Instant::now()));
+                        },
+                        Action::ResetSaturation =>
+                        {
+                            focused_control = ControlKind::Saturation;
+                            controls.saturation.reset(&mut camera);
+                            flash = Some(("SATURATION RESET".to_owned(), Instant::now()));
+                        },
+                        Action::ResetExposure =>
+                        {
+                            focused_control = ControlKind::Exposure;
+                            controls.exposure.reset(&mut camera);
+                            flash = Some(("EXPOSURE RESET".to_owned(), Instant::now()));
+                        },
+                        Action::ResetWhiteBalance =>
+                        {
+                            focused_control = ControlKind::WhiteBalance;
+                            controls.white_balance.reset(&mut camera);
+                            flash = Some(("WHITE BALANCE RESET".to_owned(), Instant::now()));
+                        },
+                        Action::ResetGain =>
+                        {
+                            focused_control = ControlKind::Gain;
+                            controls.gain.reset(&mut camera);
+                            flash = Some(("GAIN RESET".to_owned(), Instant::now()));
+                        },
+                        Action::ResetFocus =>
+                        {
+                            focused_control = ControlKind::Focus;
+                            controls.auto_focus.reset(&mut camera);
+                            controls.focus.reset(&mut camera);
+                            flash = Some(("FOCUS RESET".to_owned(), Instant::now()));
+                        },
+                        Action::ResetZoom =>
+                        {
+                            focused_control = ControlKind::Zoom;
+                            controls.zoom.reset(&mut camera);
+                            flash = Some(("ZOOM RESET".to_owned(), Instant::now()));
+                        },
+                        Action::ResetPanTilt =>
+                        {
+                            focused_control = ControlKind::Pan;
+                            controls.pan.reset(&mut camera);
+                            controls.tilt.reset(&mut camera);
+                            flash = Some(("PAN TILT RESET".to_owned(), Instant::now()));
+                        }
+                    }
+
+                    title_delay = 0;
+                },
+                _ => ()
+            }
+        }
+
+        entry_open.store(value_entry.is_some(), Ordering::Relaxed);
+
+        if let Some(target) = adaptive_fps.filter(|_| !paused && resolution_request.is_none())
+        {
+            let fps = 1000.0 / averager.average();
+
+            let can_raise = adaptive_ceiling.is_some_and(|ceiling| camera.resolution() < ceiling);
+
+            let wanted = if fps < target
+            {
+                Some(true)
+            } else if fps > target + adaptive_hysteresis && can_raise
+            {
+                Some(false)
+            } else
+            {
+                None
+            };
+
+            if let Some(lower) = wanted
+            {
+                let since = match adaptive_since
+                {
+                    Some((last, since)) if last == lower => since,
+                    _ =>
+                    {
+                        let now = Instant::now();
+                        adaptive_since = Some((lower, now));
+
+                        now
+                    }
+                };
+
+                if since.elapsed() >= adaptive_time
+                {
+                    adaptive_since = None;
+
+                    if let Some(resolution) = adjacent_resolution(&mut camera, lower)
+                    {
+                        if lower
+                        {
+                            adaptive_ceiling.get_or_insert(camera.resolution());
+                        }
+
+                        resolution_request = Some((resolution, true));
+                    }
+                }
+            } else
+            {
+                adaptive_since = None;
+            }
+        }
+
+        if let Some((resolution, automatic)) = resolution_request.take()
+        {
+            match switch_resolution(&mut camera, resolution)
+            {
+                Ok(()) =>
+                {
+                    let resolution = camera.resolution();
+
+                    format_text = format_description(&camera);
+                    if verbose
+                    {
+                        println!("switched resolution, format: {}", camera.camera_format());
+                    }
+
+                    if !automatic || adaptive_ceiling.is_some_and(|ceiling| resolution >= ceiling)
+                    {
+                        adaptive_ceiling = None;
+                    }
+
+                    controls = Controls::new(&camera);
+                    gamma_pi.reset();
+                    last_correction = None;
+                    gamma_mode.camera_changed(controls.gamma.current());
+
+                    paused_image = None;
+
+                    tx.send(ProgramMessage::ResizeWindow(resolution.width(), resolution.height())).unwrap();
+
+                    let prefix = if automatic { "AUTO " } else { "" };
+                    flash = Some((format!("{prefix}RESOLUTION {resolution}"), Instant::now()));
+                },
+                Err(err) =>
+                {
+                    eprintln!("error switching to {resolution}: {err}");
+
+                    flash = Some((format!("{resolution} UNAVAILABLE"), Instant::now()));
+                }
+            }
+        }
+
+        drop(camera);
+
+        if resized
+        {
+            tx.send(ProgramMessage::SetClosestAspect).unwrap();
+            resized = false;
+        }
+
+        let direct_allowed = !paused
+            && !yuyv_decode
+            && gamma_mode != GammaMode::Auto
+            && software_gamma.is_none()
+            && night.is_none()
+            && !(mirrored || flipped_vertical)
+            && rotation == 0
+            && !(grayscale || inverted || sepia || sharpened || blurred || edges)
+            && !software_white_balance
+            && !auto_levels
+            && !vignette_corrected
+            && !undistort
+            && isolated_channel == Channel::All
+            && !posterized
+            && !mosaic
+            && !scan_mode
+            && idle_timeout.is_none()
+            && calibration.is_none()
+            && !reference_requested
+            && reference_image.is_none()
+            && !show_histogram
+            && !show_rgb_histogram
+            && !show_waveform
+            && !false_color
+            && !show_zebra
+            && roi.is_none()
+            && roi_start.is_none()
+            && roi_selection.is_none()
+            && !show_timestamp
+            && !snapshot_requested
+            && !copy_requested
+            && !motion_requested
+            && !still_snapshot
+            && recording.is_none()
+            && replay.is_none()
+            && burst.is_none()
+            && timelapse_interval.is_none()
+            && !server.as_ref().is_some_and(MjpegServer::has_clients);
+
+        direct_yuyv.store(direct_allowed, Ordering::Relaxed);
+
+        let mut direct_frame: Option<FrameData> = None;
+
+        let mut image = if let (true, Some(image)) = (paused, paused_image.as_ref())
+        {
+            thread::sleep(Duration::from_millis(1000 / update_fps as u64));
+
+            image.clone()
+        } else
+        {
+            let image = match frame_rx.recv()
+            {
+                Ok(Ok(CapturedFrame::Rgb(x))) => x,
+                Ok(Ok(CapturedFrame::Yuyv(buffer))) =>
+                {
+                    let Resolution{width_x: width, height_y: height} = buffer.resolution();
+                    let complete = buffer.buffer().len() == width as usize * height as usize * 2;
+
+                    if direct_allowed && complete
+                    {
+                        direct_frame = Some(FrameData::Yuyv{width, height, data: buffer.buffer().to_vec()});
+
+                        // direct frames skip the rgb pipeline entirely
+                        RgbImage::new(0, 0)
+                    } else
+                    {
+                        match buffer.decode_image::<RgbFormat>()
                         {
-                            f(CropControl::ZoomXMinus)
+                            Ok(x) => x,
+                            Err(err) =>
+                            {
+                                eprintln!("error decoding the frame: {err}");
+                                dropped_frames += 1;
+
+                                continue;
+                            }
                         }
-                    },
-                    Keycode::Equals =>
+                    }
+                },
+                Ok(Err(CaptureError::Decode(err))) =>
+                {
+                    eprintln!("error decoding the frame: {err}");
+                    dropped_frames += 1;
+
+                    continue;
+                },
+                Ok(Err(CaptureError::Frame(err))) =>
+                {
+                    eprintln!("error getting a frame: {err}");
+                    dropped_frames += 1;
+
+                    frame_errors += 1;
+                    if frame_errors < RECONNECT_AFTER_ERRORS
                     {
-                        if ignore_shift
+                        continue;
+                    }
+
+                    frame_errors = 0;
+
+                    if !reconnecting
+                    {
+                        reconnecting = true;
+                        tx.send(ProgramMessage::SetTitle("[RECONNECTING]".to_owned())).unwrap();
+                    }
+
+                    let mut camera = shared_camera.lock().unwrap();
+
+                    let _ = camera.stop_stream();
+                    if let Err(err) = camera.open_stream()
+                    {
+                        eprintln!("error reopening the stream: {err}");
+
+                        let found = (0..10).find_map(|i|
                         {
-                            f(CropControl::ZoomXPlus);
-                            f(CropControl::ZoomYPlus)
-                        } else if is_shift
+                            open_camera(i, camera_format, requested_fps).ok().map(|camera| (i, camera))
+                        });
+
+                        if let Some((index, found_camera)) = found
                         {
-                            f(CropControl::ZoomYPlus)
+                            camera_index = index;
+                            *camera = found_camera;
+
+                            format_text = format_description(&camera);
+                            if verbose
+                            {
+                                println!("reconnected to camera {index}, format: {}", camera.camera_format());
+                            }
+
+                            controls = Controls::new(&camera);
+                            gamma_pi.reset();
+                            last_correction = None;
+                            gamma_mode.camera_changed(controls.gamma.current());
                         } else
                         {
-                            f(CropControl::ZoomXPlus)
+                            thread::sleep(Duration::from_millis(500));
                         }
-                    },
-                    Keycode::Up if is_shift || ignore_shift => f(CropControl::ShiftYPlus),
-                    Keycode::Down if is_shift || ignore_shift => f(CropControl::ShiftYMinus),
-                    Keycode::Right if is_shift || ignore_shift => f(CropControl::ShiftXPlus),
-                    Keycode::Left if is_shift || ignore_shift => f(CropControl::ShiftXMinus),
-                    _ => false
+                    } else if verbose
+                    {
+                        println!("reopened camera {camera_index}, format: {}", camera.camera_format());
+                    }
+
+                    continue;
+                },
+                Err(_) => break 'window_loop
+            };
+
+            frame_errors = 0;
+            reconnecting = false;
+
+            if paused
+            {
+                paused_image = Some(image.clone());
+            }
+
+            image
+        };
+
+        if undistort
+        {
+            let (width, height) = image.dimensions();
+
+            if undistort_map.as_ref().map(|map| map.size() != (width, height)).unwrap_or(true)
+            {
+                undistort_map = Some(UndistortMap::new(width, height, distortion.0, distortion.1));
+            }
+
+            image = undistort_map.as_ref().unwrap().apply(&image);
+        }
+
+        if vignette_corrected
+        {
+            let (width, height) = image.dimensions();
+
+            if vignette_map.as_ref().map(|map| map.size() != (width, height)).unwrap_or(true)
+            {
+                vignette_map = Some(VignetteMap::new(width, height, vignette_strength));
+            }
+
+            vignette_map.as_ref().unwrap().apply(&mut image);
+        }
+
+        if mirrored || flipped_vertical
+        {
+            let mut flipped = DynamicImage::from(image);
+
+            if mirrored
+            {
+                flipped = flipped.fliph();
+            }
+
+            if flipped_vertical
+            {
+                flipped = flipped.flipv();
+            }
+
+            image = flipped.to_rgb8();
+        }
+
+        if rotation != 0
+        {
+            let rotated = DynamicImage::from(image);
+
+            image = match rotation
+            {
+                90 => rotated.rotate90(),
+                180 => rotated.rotate180(),
+                270 => rotated.rotate270(),
+                x => unreachable!("rotation must be a multiple of 90, got {x}")
+            }.to_rgb8();
+        }
+
+        let selection = roi_selection.take_if(|_| direct_frame.is_none());
+        if let Some(((start_x, start_y), (end_x, end_y))) = selection
+        {
+            let (width, height) = image.dimensions();
+            let (base_x, base_y, base_width, base_height) = roi.unwrap_or((0, 0, width, height));
+
+            let to_x = |u: f32| base_x + (u.clamp(0.0, 1.0) * base_width as f32) as u32;
+            let to_y = |v: f32| base_y + (v.clamp(0.0, 1.0) * base_height as f32) as u32;
+
+            let (left, right) = (to_x(start_x.min(end_x)), to_x(start_x.max(end_x)));
+            let (top, bottom) = (to_y(start_y.min(end_y)), to_y(start_y.max(end_y)));
+
+            if right - left >= 2 && bottom - top >= 2
+            {
+                roi = Some((left, top, right - left, bottom - top));
+            }
+        }
+
+        if let Some((x, y, width, height)) = roi
+        {
+            let fits = image.width() >= x + width && image.height() >= y + height;
+
+            if fits && direct_frame.is_none()
+            {
+                image = imageops::crop_imm(&image, x, y, width, height).to_image();
+            } else if !fits
+            {
+                roi = None;
+            }
+        }
+
+        if let (Some(sweep), false) = (calibration.as_mut(), paused)
+        {
+            if sweep.settle > 0
+            {
+                sweep.settle -= 1;
+            } else
+            {
+                let brightness = average_brightness(&image, center_weighted, metering_stride);
+                let difference = (target_brightness - brightness).abs();
+
+                if sweep.best.is_none_or(|(_, best)| difference < best)
+                {
+                    sweep.best = Some((sweep.values[sweep.index], difference));
+                }
+
+                sweep.index += 1;
+
+                let mut camera = shared_camera.lock().unwrap();
+                if let Some(&value) = sweep.values.get(sweep.index)
+                {
+                    controls.gamma.set(&mut camera, value);
+                    sweep.settle = calibration_settle;
+                } else
+                {
+                    let (best, _) = sweep.best.unwrap();
+
+                    controls.gamma.set(&mut camera, best);
+                    gamma_mode = GammaMode::Manual{fullbright: false, current: best};
+
+                    println!("calibrated gamma: {best}");
+                    flash = Some((format!("GAMMA {best}"), Instant::now()));
+
+                    calibration = None;
                 }
             }
+        }
+
+        let auto_supported = controls.gamma.is_supported() || controls.exposure.is_supported();
+        let metering_frame = frame_index.is_multiple_of(gamma_interval);
+        if gamma_mode == GammaMode::Auto && !exposure_locked && !paused && auto_supported && metering_frame
+        {
+            let average_brightness = if grid_metering
+            {
+                grid_brightness(&image, 4, metering_stride)
+            } else
+            {
+                average_brightness(&image, center_weighted, metering_stride)
+            };
+
+            let brightness_diff = target_brightness - average_brightness;
+
+            let direction = brightness_diff.signum() as i64;
+
+            let control = if controls.exposure.can_move(direction)
+            {
+                &mut controls.exposure
+            } else if auto_gain && controls.gain.can_move(direction)
+            {
+                &mut controls.gain
+            } else
+            {
+                &mut controls.gamma
+            };
+
+            let reversing = last_correction.is_some_and(|last| last != direction);
+            let threshold = if reversing
+            {
+                brightness_range + brightness_hysteresis
+            } else
+            {
+                brightness_range
+            };
+
+            if brightness_diff.abs() > threshold && control.is_supported()
+            {
+                last_correction = Some(direction);
+
+                let correction = gamma_pi.update(brightness_diff) * control.range() as f64;
+
+                let delta = if correction.round() == 0.0
+                {
+                    direction
+                } else
+                {
+                    correction.round() as i64
+                };
+
+                control.set(&mut shared_camera.lock().unwrap(), control.current() + delta);
+            } else
+            {
+                gamma_pi.reset();
+            }
+        }
+
+        let help = show_help.then(||
+        {
+            let on_off = |x: bool|
+            {
+                if x { "on" } else { "off" }.to_owned()
+            };
+
+            let supported = |control: &ControlController|
+            {
+                if control.is_supported() { control.current().to_string() } else { "unsupported".to_owned() }
+            };
+
+            let mut bindings: Vec<_> = key_bindings.iter().collect();
+            bindings.sort_by_key(|(_, action)| **action);
+
+            let mut lines: Vec<String> = bindings.into_iter().map(|(key, action)|
+            {
+                let state = match action
+                {
+                    Action::Mirror => Some(on_off(mirrored)),
+                    Action::FlipVertical => Some(on_off(flipped_vertical)),
+                    Action::Rotate => Some(format!("{rotation}°")),
+                    Action::Burst => Some(format!("{burst_frames} frames")),
+                    Action::AlwaysOnTop => Some(on_off(always_on_top)),
+                    Action::Replay => Some(replay.as_ref().map(|frames|
+                    {
+                        format!("{} of {replay_frames} frames", frames.len())
+                    }).unwrap_or_else(|| "off".to_owned())),
+                    Action::Record => Some(format!("{}, up to {max_recording_frames} frames", on_off(recording.is_some()))),
+                    Action::Pause => Some(on_off(paused)),
+                    Action::Histogram => Some(on_off(show_histogram)),
+                    Action::RgbHistogram => Some(on_off(show_rgb_histogram)),
+                    Action::Waveform => Some(on_off(show_waveform)),
+                    Action::FalseColor => Some(on_off(false_color)),
+                    Action::ClearRegion => Some(roi.map(|(x, y, width, height)|
+                    {
+                        format!("{width}x{height} at {x}, {y}")
+                    }).unwrap_or_else(|| "full frame".to_owned())),
+                    Action::Zebra => Some(format!("{}, above {zebra_threshold}", on_off(show_zebra))),
+                    Action::Grayscale => Some(on_off(grayscale)),
+                    Action::Invert => Some(on_off(inverted)),
+                    Action::Sepia => Some(on_off(sepia)),
+                    Action::Sharpen => Some(on_off(sharpened)),
+                    Action::Blur => Some(on_off(blurred)),
+                    Action::Edges => Some(on_off(edges)),
+                    Action::SoftwareWhiteBalance => Some(on_off(software_white_balance)),
+                    Action::AutoLevels => Some(on_off(auto_levels)),
+                    Action::Compare => Some(on_off(reference_image.is_some())),
+                    Action::Posterize => Some(format!("{}, {posterize_levels} levels", on_off(posterized))),
+                    Action::Mosaic => Some(format!("{}, {}", on_off(mosaic), if mosaic_area.is_some()
+                    {
+                        "selected area"
+                    } else
+                    {
+                        "whole frame"
+                    })),
+                    Action::MosaicSize => Some(format!("{mosaic_block} pixels")),
+                    Action::Scan => Some(format!("{}, {scan_window} pixel window", on_off(scan_mode))),
+                    Action::NextResolution => Some(format_text.clone()),
+                    Action::IsolateChannel => Some(format!("{isolated_channel:?}")),
+                    Action::EnterValue => Some(focused_control.name().to_owned()),
+                    Action::Vignette => Some(format!("{}, strength {vignette_strength}", on_off(vignette_corrected))),
+                    Action::Grid => Some(on_off(show_grid)),
+                    Action::Timestamp => Some(on_off(show_timestamp)),
+                    Action::TimestampCorner => Some(format!("{timestamp_corner:?}")),
+                    Action::Crosshair => Some(on_off(show_crosshair)),
+                    Action::CenterWeighted => Some(on_off(center_weighted)),
+                    Action::GridMetering => Some(on_off(grid_metering)),
+                    Action::ToggleGammaMode => Some(on_off(gamma_mode == GammaMode::Auto)),
+                    Action::ExposureLock => Some(on_off(exposure_locked)),
+                    Action::Fullbright => Some(on_off(matches!(gamma_mode, GammaMode::Manual{fullbright: true, ..}))),
+                    Action::SoftwareGamma => Some(on_off(software_gamma.is_some())),
+                    Action::NightMode => Some(on_off(night.is_some())),
+                    Action::ScaleMode => Some(format!("{scale_mode:?}").to_lowercase()),
+                    Action::Calibrate => Some(on_off(calibration.is_some())),
+                    Action::GammaUp | Action::GammaDown => Some(software_gamma.as_ref().map(|gamma_lut|
+                    {
+                        format!("{:.2}", gamma_lut.gamma())
+                    }).unwrap_or_else(|| controls.gamma.current().to_string())),
+                    Action::ContrastUp | Action::ContrastDown => Some(controls.contrast.current().to_string()),
+                    Action::SaturationUp | Action::SaturationDown => Some(controls.saturation.current().to_string()),
+                    Action::ExposureUp | Action::ExposureDown => Some(controls.exposure.current().to_string()),
+                    Action::WhiteBalanceUp | Action::WhiteBalanceDown => Some(controls.white_balance.current().to_string()),
+                    Action::NextCamera => Some(format!("camera {camera_index}")),
+                    Action::ResetDropped => Some(dropped_frames.to_string()),
+                    Action::FrameCounter => Some(on_off(show_counter)),
+                    Action::StatusBar => Some(on_off(show_status)),
+                    Action::ResetCounter => Some(shown_frames.to_string()),
+                    Action::FocusIn | Action::FocusOut => Some(if controls.focus.is_supported()
+                    {
+                        controls.focus.current().to_string()
+                    } else
+                    {
+                        "unsupported".to_owned()
+                    }),
+                    Action::PanTilt => Some(if controls.pan.is_supported() || controls.tilt.is_supported()
+                    {
+                        on_off(ptz_mode)
+                    } else
+                    {
+                        "unsupported".to_owned()
+                    }),
+                    Action::ZoomIn | Action::ZoomOut => Some(if controls.zoom.is_supported()
+                    {
+                        controls.zoom.current().to_string()
+                    } else
+                    {
+                        "unsupported".to_owned()
+                    }),
+                    Action::AutoFocus => Some(if controls.auto_focus.is_supported()
+                    {
+                        on_off(controls.auto_focus.current())
+                    } else
+                    {
+                        "unsupported".to_owned()
+                    }),
+                    Action::ResetBrightness => Some(controls.brightness.current().to_string()),
+                    Action::ResetGain => Some(supported(&controls.gain)),
+                    Action::ResetFocus => Some(supported(&controls.focus)),
+                    Action::ResetZoom => Some(supported(&controls.zoom)),
+                    Action::ResetPanTilt => Some(if controls.pan.is_supported() || controls.tilt.is_supported()
+                    {
+                        format!("{}, {}", controls.pan.current(), controls.tilt.current())
+                    } else
+                    {
+                        "unsupported".to_owned()
+                    }),
+                    Action::ResetGamma
+                        | Action::ResetContrast
+                        | Action::ResetSaturation
+                        | Action::ResetExposure
+                        | Action::ResetWhiteBalance
+                        | Action::Help
+                        | Action::Quit
+                        | Action::ResetWindow
+                        | Action::Fullscreen
+                        | Action::Snapshot
+                        | Action::CopySnapshot => None
+                };
+
+                let state = state.map(|state| format!(" ({state})")).unwrap_or_default();
+
+                format!("{:<14}{}{state}", key.name(), action.description())
+            }).collect();
+
+            if !exact_size
+            {
+                lines.push(format!("{:<14}{}", "- / =", "zoom crop, shift for vertical"));
+                lines.push(format!("{:<14}{}", "alt+arrows", "move crop"));
+                lines.push(format!("{:<14}{}", "wheel / drag", "zoom and pan"));
+            }
+
+            lines.push(format!("{:<14}{}", "middle drag", "select a region to crop to"));
+
+            if controls.gain.is_supported()
+            {
+                lines.push(format!("{:<14}gain, {}", "ctrl+up/down", controls.gain.current()));
+            }
+
+            lines.push(format!("{:<14}{}", "shift+control", "10x step"));
+
+            if borderless
+            {
+                lines.push(format!("{:<14}{}", "right drag", "move window"));
+            }
+
+            lines.push(format!("{:<14}{}", "format", format_text));
+
+            lines
+        });
+
+        let mut overlays = Overlays{
+            help,
+            histogram: show_histogram.then(|| luminance_histogram(&image)),
+            rgb_histogram: None,
+            waveform: show_waveform.then(|| luminance_waveform(&image)),
+            crosshair: show_crosshair.then_some(crosshair_color),
+            grid: show_grid,
+            status: show_status.then(|| status_line.clone())
+        };
+
+        let white_balance_means = software_white_balance.then(|| filters::channel_means(&image));
+
+        if let Some(gamma_lut) = software_gamma.as_ref()
+        {
+            gamma_lut.apply(&mut image);
+        }
+
+        if let Some(night) = night.as_ref()
+        {
+            night.boost.apply(&mut image);
+        }
+
+        if auto_levels
+        {
+            levels_delay -= 1;
+            if levels.is_none() || levels_delay <= 0
+            {
+                levels = Some(levels_range(&image, 0.01));
+
+                levels_delay = 10;
+            }
+
+            if let Some((low, high)) = levels
+            {
+                filters::stretch_levels(&mut image, low, high);
+            }
+        }
+
+        if blurred
+        {
+            filters::box_blur(&mut image);
+        }
+
+        if grayscale
+        {
+            image = DynamicImage::from(image).grayscale().to_rgb8();
+        }
+
+        if sepia
+        {
+            filters::sepia(&mut image);
+        }
+
+        if posterized
+        {
+            filters::posterize(&mut image, posterize_levels);
+        }
+
+        if inverted
+        {
+            imageops::invert(&mut image);
+        }
+
+        if sharpened
+        {
+            image = filters::sharpen(&image);
+        }
+
+        if edges
+        {
+            image = filters::sobel(&image);
+        }
+
+        if let Some(means) = white_balance_means
+        {
+            filters::white_balance(&mut image, means);
+        }
+
+        if let Some(index) = isolated_channel.index()
+        {
+            filters::isolate_channel(&mut image, index);
+        }
+
+        if scan_mode
+        {
+            image = filters::adaptive_threshold(&image, scan_window);
+        }
+
+        // the channels as shown, after software gamma and the filters
+        overlays.rgb_histogram = show_rgb_histogram.then(|| channel_histograms(&image));
+
+        if motion_needed && direct_frame.is_none()
+        {
+            let (size, plane) = motion_plane(&image);
+
+            let amount = previous_plane.as_ref().filter(|(previous_size, _)| *previous_size == size)
+                .map(|(_, previous)| motion_amount(previous, &plane));
+
+            previous_plane = Some((size, plane));
+
+            let moved = amount.is_some_and(|amount| amount > motion_threshold);
+
+            if moved
+            {
+                last_activity = Instant::now();
+            }
 
-            loop
+            if still_snapshot && !paused
             {
-                let received: Option<ProgramMessage> = match rx.try_recv()
+                if amount.is_some_and(|amount| amount < still_threshold)
                 {
-                    Ok(x) => Some(x),
-                    Err(TryRecvError::Empty) => None,
-                    _ => return
-                };
+                    let since = *still_since.get_or_insert_with(Instant::now);
 
-                for event in events.poll_iter()
-                {
-                    match event
+                    if since.elapsed() >= still_time && !still_captured
                     {
-                        Event::KeyUp{keycode: Some(code), keymod, ..} =>
-                        {
-                            let handled = crop_control_of(code, keymod, true, |c|
-                            {
-                                crop_controls[c as usize] = false;
+                        still_captured = true;
+                        snapshot_requested = true;
 
-                                true
-                            });
+                        flash = Some(("STILL SNAPSHOT".to_owned(), Instant::now()));
+                    }
+                } else if amount.is_some()
+                {
+                    still_since = None;
+                    still_captured = false;
+                }
+            }
 
-                            if handled
-                            {
-                                continue;
-                            }
-                        },
-                        Event::KeyDown{keycode: Some(code), keymod, ..} =>
-                        {
-                            let handled = crop_control_of(code, keymod, false, |c|
-                            {
-                                crop_controls[c as usize] = true;
+            if moved && !motion_detected && motion_snapshot
+            {
+                snapshot_requested = true;
+            }
 
-                                true
-                            });
+            motion_detected = moved;
+        } else
+        {
+            previous_plane = None;
+            motion_detected = false;
+        }
 
-                            if handled
-                            {
-                                continue;
-                            }
-                        },
-                        _ => ()
-                    }
+        if reference_requested
+        {
+            reference_requested = false;
 
-                    slow_events.lock().unwrap().push(event);
-                }
+            reference_image = Some(image.clone());
+        }
 
-                let update_aspect = |canvas: &mut _, crop_info: CropInfo|
-                {
-                    let aspect = (width as f64 * crop_info.scale_x as f64)
-                        / (height as f64 * crop_info.scale_y as f64);
+        if let Some(reference) = reference_image.as_ref()
+        {
+            if reference.dimensions() == image.dimensions()
+            {
+                filters::split_compare(&mut image, reference);
+            }
+        }
 
-                    set_closest_aspect(canvas, aspect)
-                };
+        if mosaic
+        {
+            let (width, height) = image.dimensions();
 
-                let dt: f32 = 1.0 / update_fps as f32;
+            let area = mosaic_area.map(|((start_x, start_y), (end_x, end_y))|
+            {
+                let to_x = |u: f32| (u.clamp(0.0, 1.0) * width as f32) as u32;
+                let to_y = |v: f32| (v.clamp(0.0, 1.0) * height as f32) as u32;
 
-                let zoom_speed = 0.25;
+                let (left, top) = (to_x(start_x.min(end_x)), to_y(start_y.min(end_y)));
 
-                let zoom_in_factor = 1.0 - dt * zoom_speed;
-                let zoom_out_factor = 1.0 + dt * zoom_speed;
+                (left, top, to_x(start_x.max(end_x)) - left, to_y(start_y.max(end_y)) - top)
+            }).unwrap_or((0, 0, width, height));
 
-                let c = |x| crop_controls[x as usize];
+            filters::mosaic(&mut image, mosaic_block, area);
+        }
 
-                let low_zoom = 0.01;
+        if show_timestamp
+        {
+            let timestamp = Local::now().format("%Y-%m-%d %H:%M:%S").to_string();
 
-                if c(CropControl::ZoomXPlus)
-                {
-                    crop_info.scale_x = (crop_info.scale_x * zoom_in_factor).clamp(low_zoom, 1.0);
-                }
+            filters::burn_in_text(&mut image, &timestamp, timestamp_corner);
+        }
 
-                if c(CropControl::ZoomYPlus)
-                {
-                    crop_info.scale_y = (crop_info.scale_y * zoom_in_factor).clamp(low_zoom, 1.0);
-                }
+        if snapshot_requested
+        {
+            snapshot_requested = false;
 
-                if c(CropControl::ZoomXMinus)
-                {
-                    crop_info.scale_x = (crop_info.scale_x * zoom_out_factor).clamp(low_zoom, 1.0);
-                }
+            save_snapshot(&image, quality);
+            tx.send(ProgramMessage::Beep).unwrap();
+        }
 
-                if c(CropControl::ZoomYMinus)
-                {
-                    crop_info.scale_y = (crop_info.scale_y * zoom_out_factor).clamp(low_zoom, 1.0);
-                }
+        if copy_requested
+        {
+            copy_requested = false;
 
-                let change_pos = |value: &mut f32, amount: f32, zoom: f32|
-                {
-                    let half_zoom = zoom * 0.5;
+            if copy_to_clipboard(&image)
+            {
+                flash = Some(("COPIED".to_owned(), Instant::now()));
+            } else
+            {
+                eprintln!("no clipboard image support, saving instead");
+                save_snapshot(&image, quality);
 
-                    let low = half_zoom;
-                    let high = 1.0 - half_zoom;
+                flash = Some(("SAVED".to_owned(), Instant::now()));
+            }
+        }
 
-                    *value = (*value + amount * zoom).clamp(low, high);
-                };
+        if let Some(interval) = timelapse_interval
+        {
+            let due = last_timelapse.map(|last| last.elapsed() >= interval).unwrap_or(true);
 
-                let move_speed = 0.8 * dt;
+            if due && !paused
+            {
+                last_timelapse = Some(Instant::now());
 
-                if c(CropControl::ShiftXPlus)
-                {
-                    change_pos(&mut crop_info.pos_x, move_speed, crop_info.scale_x);
-                }
+                let filename = format!("timelapse_{timelapse_start}_{timelapse_index:05}.png");
+                save_image(&image, "timelapse frame", &filename, quality);
 
-                if c(CropControl::ShiftXMinus)
+                timelapse_index += 1;
+            }
+        }
+
+        if let Some(frames) = recording.as_mut()
+        {
+            let same_size = frames.first().map(|first|
+            {
+                first.dimensions() == image.dimensions()
+            }).unwrap_or(true);
+
+            if same_size && !paused
+            {
+                frames.push(image.clone());
+            }
+
+            if frames.len() >= max_recording_frames
+            {
+                println!("recording reached {max_recording_frames} frames, stopping");
+
+                encoding_threads.push(save_recording("recording", recording.take().unwrap(), averager.average(), quality));
+                flash = Some(("RECORDING STOPPED AT LIMIT".to_owned(), Instant::now()));
+                tx.send(ProgramMessage::Beep).unwrap();
+            }
+        }
+
+        if let Some(frames) = replay.as_mut()
+        {
+            if frames.front().is_some_and(|first: &RgbImage| first.dimensions() != image.dimensions())
+            {
+                frames.clear();
+            }
+
+            if !paused
+            {
+                if frames.len() >= replay_frames
                 {
-                    change_pos(&mut crop_info.pos_x, -move_speed, crop_info.scale_x);
+                    frames.pop_front();
                 }
 
-                if c(CropControl::ShiftYMinus)
-                {
-                    change_pos(&mut crop_info.pos_y, move_speed, crop_info.scale_y);
-                }
+                frames.push_back(image.clone());
+            }
+        }
+
+        if let Some(frames) = burst.as_mut()
+        {
+            if !paused
+            {
+                frames.push(image.clone());
+            }
+
+            if frames.len() >= burst_frames
+            {
+                encoding_threads.push(save_burst(burst.take().unwrap(), quality));
+                tx.send(ProgramMessage::Beep).unwrap();
+            }
+        }
+
+        let cursor = cursor_position.lock().unwrap().filter(|_| direct_frame.is_none());
+        let spot_lightness = cursor.and_then(|(x, y)|
+        {
+            let (width, height) = image.dimensions();
+
+            let x = (x * width as f32) as i64;
+            let y = (y * height as f32) as i64;
+
+            let inside = (0..width as i64).contains(&x) && (0..height as i64).contains(&y);
+
+            inside.then(|| lightness(pixel_luminance(image.get_pixel(x as u32, y as u32))))
+        });
+
+        if direct_frame.is_some()
+        {
+            scene_temperature = None;
+        } else if title_delay <= 1
+        {
+            scene_temperature = color_temperature(&image);
+        }
+
+        if let (Some(server), None) = (server.as_ref(), direct_frame.as_ref())
+        {
+            server.send(&image);
+        }
+
+        if false_color
+        {
+            filters::false_color(&mut image);
+        }
+
+        if show_zebra
+        {
+            filters::zebra(&mut image, zebra_threshold, frame_index);
+        }
+
+        let dimmed = idle_timeout.is_some_and(|timeout| last_activity.elapsed() >= timeout);
+        if dimmed
+        {
+            image.iter_mut().for_each(|value| *value /= 4);
+        }
+
+        let skipped_rgb = skipped_frames[0].swap(0, Ordering::Relaxed);
+        let skipped_yuyv = skipped_frames[1].swap(0, Ordering::Relaxed);
+
+        // nothing takes frames while paused, so the capture thread skipping them isnt a drop
+        if !paused
+        {
+            dropped_frames += skipped_rgb + skipped_yuyv;
+            dropped_yuyv += skipped_yuyv;
+        }
+
+        let frame = direct_frame.take().unwrap_or_else(|| FrameData::Rgb(Box::new(image)));
+        let direct = matches!(frame, FrameData::Yuyv{..});
+        yuyv_seen |= direct;
+        let (frame_width, frame_height) = frame.size();
+
+        tx.send(ProgramMessage::Render(frame, overlays)).unwrap();
+        shown_frames += 1;
+
+        title_delay -= 1;
+        if title_delay <= 0
+        {
+            let fps = 1000.0 / averager.average();
+            let gamma = controls.gamma.current();
+            let contrast = controls.contrast.current();
+            let saturation = controls.saturation.current();
+            let exposure = controls.exposure.current();
+            let white_balance = controls.white_balance.current();
+
+            let poster_tag = posterized.then(|| format!("POSTER {posterize_levels}"));
+
+            let roi_tag = roi.map(|(_, _, width, height)| format!("ROI {width}x{height}"));
+
+            let entry_tag = value_entry.as_ref().map(|entry|
+            {
+                format!("SET {}: {entry}_", focused_control.name().to_uppercase())
+            });
+
+            let burst_tag = burst.as_ref().map(|frames| format!("BURST {}/{burst_frames}", frames.len()));
+
+            let mut tags = Vec::new();
+
+            match gamma_mode
+            {
+                GammaMode::Auto => tags.push("AUTO"),
+                GammaMode::Manual{fullbright: true, ..} => tags.push("FULLBRIGHT"),
+                GammaMode::Manual{..} => ()
+            }
+
+            if exposure_locked && gamma_mode == GammaMode::Auto
+            {
+                tags.push("LOCK");
+            }
+
+            if night.is_some()
+            {
+                tags.push("NIGHT");
+            }
+
+            if calibration.is_some()
+            {
+                tags.push("CALIBRATING");
+            }
+
+            if grid_metering
+            {
+                tags.push("ZONES");
+            } else if center_weighted
+            {
+                tags.push("CENTER");
+            }
+
+            if mirrored || flipped_vertical
+            {
+                tags.push("FLIP");
+            }
+
+            if grayscale
+            {
+                tags.push("GRAY");
+            }
+
+            if inverted
+            {
+                tags.push("INV");
+            }
+
+            if sepia
+            {
+                tags.push("SEPIA");
+            }
+
+            if sharpened
+            {
+                tags.push("SHARP");
+            }
+
+            if blurred
+            {
+                tags.push("BLUR");
+            }
+
+            if edges
+            {
+                tags.push("EDGE");
+            }
+
+            if software_white_balance
+            {
+                tags.push("AWB");
+            }
+
+            if auto_levels
+            {
+                tags.push("LEVELS");
+            }
+
+            if vignette_corrected
+            {
+                tags.push("FLAT");
+            }
+
+            if reference_image.is_some()
+            {
+                tags.push("A/B");
+            }
+
+            match isolated_channel
+            {
+                Channel::All => (),
+                Channel::Red => tags.push("RED"),
+                Channel::Green => tags.push("GREEN"),
+                Channel::Blue => tags.push("BLUE")
+            }
+
+            if recording.is_some()
+            {
+                tags.push("REC");
+            }
+
+            if motion_detected
+            {
+                tags.push("MOTION");
+            }
+
+            if direct
+            {
+                tags.push("YUYV");
+            }
+
+            if let Some((text, _)) = flash.as_ref().filter(|(_, start)| start.elapsed() < flash_duration)
+            {
+                tags.push(text);
+            }
+
+            if let Some(burst_tag) = burst_tag.as_ref()
+            {
+                tags.push(burst_tag);
+            }
+
+            if let Some(entry_tag) = entry_tag.as_ref()
+            {
+                tags.push(entry_tag);
+            }
+
+            if let Some(poster_tag) = poster_tag.as_ref()
+            {
+                tags.push(poster_tag);
+            }
+
+            if ptz_mode
+            {
+                tags.push("PTZ");
+            }
+
+            if mosaic
+            {
+                tags.push("MOSAIC");
+            }
+
+            if scan_mode
+            {
+                tags.push("SCAN");
+            }
 
-                if c(CropControl::ShiftYPlus)
-                {
-                    change_pos(&mut crop_info.pos_y, -move_speed, crop_info.scale_y);
-                }
+            if adaptive_fps.is_some()
+            {
+                tags.push(if adaptive_ceiling.is_some() { "ADAPTIVE LOWERED" } else { "ADAPTIVE" });
+            }
 
-                if c(CropControl::ZoomXPlus)
-                    || c(CropControl::ZoomXMinus)
-                    || c(CropControl::ZoomYPlus)
-                    || c(CropControl::ZoomYMinus)
-                {
-                    update_aspect(&mut canvas, crop_info);
-                }
+            if let Some(roi_tag) = roi_tag.as_ref()
+            {
+                tags.push(roi_tag);
+            }
 
-                if let Some(received) = received
-                {
-                    match received
-                    {
-                        ProgramMessage::Render(image) =>
-                        {
-                            let original_width = image.width();
-                            let original_height = image.height();
+            if false_color
+            {
+                tags.push("FALSECOLOR");
+            }
 
-                            let mut data = image.into_raw();
+            if exact_size
+            {
+                tags.push("EXACT SIZE");
+            }
 
-                            if camera_texture.is_none()
-                            {
-                                camera_texture = Some(texture_creator.create_texture_streaming(
-                                    PixelFormatEnum::RGB24,
-                                    original_width,
-                                    original_height
-                                ).unwrap());
-                            }
+            match scale_mode
+            {
+                ScaleMode::Fit => (),
+                ScaleMode::Fill => tags.push("FILL"),
+                ScaleMode::Stretch => tags.push("STRETCH")
+            }
 
-                            {
-                                let camera_texture = camera_texture.as_mut().unwrap();
+            if dimmed
+            {
+                tags.push("DIM");
+            }
 
-                                camera_texture.update(
-                                    None,
-                                    &mut data,
-                                    (original_width * 3) as usize
-                                ).unwrap();
-                            }
+            if paused
+            {
+                tags.push("PAUSED");
+            }
 
-                            let camera_texture = camera_texture.as_ref().unwrap();
+            if let Some(error) = camera_error.as_ref()
+            {
+                tags.push(error);
+            }
 
-                            let width = (original_width as f32 * crop_info.scale_x) as u32;
-                            let height = (original_height as f32 * crop_info.scale_y) as u32;
+            let tags: String = tags.iter().map(|tag| format!("[{tag}] ")).collect();
 
-                            let pos_of = |p: f32, s: f32, original: u32| -> i32
-                            {
-                                ((p - s * 0.5) * original as f32) as i32
-                            };
+            let gamma = if let Some(gamma_lut) = software_gamma.as_ref()
+            {
+                format!("{:.2} SW gamma", gamma_lut.gamma())
+            } else
+            {
+                format!("{gamma} HW gamma")
+            };
 
-                            let cropped_rect = Rect::new(
-                                pos_of(crop_info.pos_x, crop_info.scale_x, original_width),
-                                pos_of(crop_info.pos_y, crop_info.scale_y, original_height),
-                                width,
-                                height
-                            );
+            let mut title_extras = String::new();
 
-                            canvas.copy(camera_texture, Some(cropped_rect), None).unwrap();
-                            canvas.present();
-                        },
-                        ProgramMessage::ResetWindow =>
-                        {
-                            if let Err(err) = canvas.window_mut().set_size(width, height)
-                            {
-                                eprintln!("error setting window size: {err}");
-                            }
+            if controls.focus.is_supported()
+            {
+                let auto = if controls.auto_focus.current() { " (auto)" } else { "" };
 
-                            crop_info = CropInfo::new();
-                        },
-                        ProgramMessage::SetClosestAspect =>
-                        {
-                            update_aspect(&mut canvas, crop_info);
-                        },
-                        ProgramMessage::SetTitle(title) =>
-                        {
-                            if let Err(err) = canvas.window_mut().set_title(&title)
-                            {
-                                eprintln!("error updating title: {err}");
-                            }
-                        }
-                    }
-                }
+                title_extras += &format!(", {}{auto} focus", controls.focus.current());
+            }
 
-                thread::sleep(Duration::from_millis(1000 / update_fps as u64));
+            if let Some(temperature) = scene_temperature
+            {
+                title_extras += &format!(", ≈{:.0}K", (temperature / 100.0).round() * 100.0);
             }
-        })
-    };
 
-    'window_loop: loop
-    {
-        for event in slow_events.lock().unwrap().drain(..)
-        {
-            match event
+            if controls.gain.is_supported()
             {
-                Event::Quit{..} => break 'window_loop,
-                Event::Window{win_event: WindowEvent::Resized(_, _), ..} =>
-                {
-                    resized = true;
-                },
-                Event::KeyDown{keycode: Some(code), keymod, ..} =>
-                {
-                    match code
-                    {
-                        Keycode::SPACE =>
-                        {
-                            tx.send(ProgramMessage::ResetWindow).unwrap();
-                        },
-                        Keycode::M =>
-                        {
-                            mirrored = !mirrored;
-                        },
-                        Keycode::G =>
-                        {
-                            gamma_control.reset(&mut camera);
-                            brightness_control.reset(&mut camera);
+                title_extras += &format!(", {} gain", controls.gain.current());
+            }
 
-                            gamma_mode = match gamma_mode
-                            {
-                                GammaMode::Manual{..} => GammaMode::Auto,
-                                GammaMode::Auto => GammaMode::Manual{fullbright: false, current: gamma_control.current()}
-                            };
-                        },
-                        Keycode::F =>
-                        {
-                            if let GammaMode::Manual{ref mut fullbright, current} = gamma_mode
-                            {
-                                *fullbright = !*fullbright;
+            if let Some(spot) = spot_lightness
+            {
+                title_extras += &format!(", spot: {spot:.0}");
+            }
 
-                                if *fullbright
-                                {
-                                    gamma_control.set_max(&mut camera);
-                                    brightness_control.set_max(&mut camera);
-                                } else
-                                {
-                                    gamma_control.set(&mut camera, current);
-                                    brightness_control.reset(&mut camera);
-                                }
-                            }
-                        },
-                        Keycode::Up | Keycode::Down if keymod != Mod::LSHIFTMOD =>
-                        {
-                            if let GammaMode::Manual{ref mut current, ..} = gamma_mode
-                            {
-                                let new_current = if let Keycode::Up = code
-                                {
-                                    *current + 1
-                                } else
-                                {
-                                    *current - 1
-                                };
+            if controls.zoom.is_supported()
+            {
+                title_extras += &format!(", {} zoom", controls.zoom.current());
+            }
 
-                                gamma_control.set(&mut camera, new_current);
-                                *current = gamma_control.current();
-                            }
-                        },
-                        _ => ()
-                    }
+            if controls.pan.is_supported()
+            {
+                title_extras += &format!(", {} pan", controls.pan.current());
+            }
 
-                    title_delay = 0;
-                },
-                _ => ()
+            if controls.tilt.is_supported()
+            {
+                title_extras += &format!(", {} tilt", controls.tilt.current());
             }
-        }
 
-        if resized
-        {
-            tx.send(ProgramMessage::SetClosestAspect).unwrap();
-            resized = false;
-        }
+            let dropped = if yuyv_seen
+            {
+                format!("{dropped_frames} ({} rgb, {dropped_yuyv} yuyv)", dropped_frames - dropped_yuyv)
+            } else
+            {
+                dropped_frames.to_string()
+            };
 
-        let frame = match camera.frame()
-        {
-            Ok(x) => x,
-            Err(err) =>
+            let gamma_state = match gamma_mode
             {
-                eprintln!("error getting a frame: {err}");
-                continue;
-            }
-        };
+                GammaMode::Auto => "auto gamma".to_owned(),
+                GammaMode::Manual{fullbright: true, ..} => "fullbright".to_owned(),
+                GammaMode::Manual{..} => gamma.clone()
+            };
 
-        let mut image = match frame.decode_image::<RgbFormat>()
-        {
-            Ok(x) => x,
-            Err(err) =>
+            status_line = format!("{fps:.1} fps  {gamma_state}  {tags}dropped: {dropped}");
+
+            let elapsed = counter_start.elapsed().as_secs_f64();
+            let counter = if show_counter
             {
-                eprintln!("error decoding the frame: {err}");
-                continue;
-            }
-        };
+                format!(", frame {shown_frames} / {elapsed:.1}s")
+            } else
+            {
+                String::new()
+            };
 
-        if mirrored
-        {
-            image = DynamicImage::from(image).fliph().to_rgb8();
+            let title = if let Some(title_format) = title_format.as_ref()
+            {
+                format_title(title_format, &[
+                    ("w", frame_width.to_string()),
+                    ("h", frame_height.to_string()),
+                    ("fps", format!("{fps:.1}")),
+                    ("gamma", gamma),
+                    ("tag", tags.trim_end().to_owned()),
+                    ("contrast", contrast.to_string()),
+                    ("saturation", saturation.to_string()),
+                    ("exposure", exposure.to_string()),
+                    ("white_balance", white_balance.to_string()),
+                    ("rotation", rotation.to_string()),
+                    ("dropped", dropped_frames.to_string()),
+                    ("frame", shown_frames.to_string()),
+                    ("elapsed", format!("{elapsed:.1}"))
+                ])
+            } else
+            {
+                format!("{fps:.1} fps{counter}, {tags}{gamma}, {contrast} contrast, {saturation} saturation, {exposure} exposure, {white_balance} white balance{title_extras}, {rotation}° rotation, dropped: {dropped}")
+            };
+
+            tx.send(ProgramMessage::SetTitle(title)).unwrap();
+
+            title_delay = 10;
         }
 
-        if gamma_mode == GammaMode::Auto
+        if last_config_check.elapsed() >= Duration::from_secs(1)
         {
-            let average_brightness = {
-                let total = (image.width() * image.height()) as f64;
+            last_config_check = Instant::now();
 
-                let luminance = image.pixels().map(|Rgb([r, g, b])|
+            let modified = Config::modified(config_camera);
+            if modified != config_modified
+            {
+                config_modified = modified;
+
+                if let Some(new_config) = Config::reload(config_camera)
                 {
-                    let d = |&x|
+                    key_bindings = bindings::load_bindings(&new_config.keybindings);
+
+                    if args.target_brightness.is_none()
                     {
-                        let value = x as f64 / u8::MAX as f64;
+                        target_brightness = validated_arg(
+                            "target brightness",
+                            new_config.target_brightness,
+                            0.0..=100.0,
+                            15.0
+                        );
+                    }
 
-                        if value < 0.04045
-                        {
-                            value / 12.92
-                        } else
-                        {
-                            ((value + 0.055) / 1.055).powf(2.4)
-                        }
-                    };
+                    // editing the filters section reapplies all of them
+                    if new_config.filters != config.filters
+                    {
+                        let filters = &new_config.filters;
+
+                        grayscale = filters.grayscale;
+                        inverted = filters.invert;
+                        sepia = filters.sepia;
+                        sharpened = filters.sharpen;
+                        blurred = filters.blur;
+                        edges = filters.edges;
+                        software_white_balance = filters.white_balance;
+                        auto_levels = filters.auto_levels;
+                        vignette_corrected = filters.vignette;
+                        posterized = filters.posterize;
+                        scan_mode = filters.scan;
+
+                        levels = None;
+                    }
 
-                    d(r) * 0.2126 + d(g) * 0.7152 + d(b) * 0.0722
-                }).sum::<f64>() / total;
+                    if args.mirror.is_none() && new_config.mirrored != config.mirrored
+                    {
+                        mirrored = new_config.mirrored;
+                    }
 
-                if luminance <= 0.008856
-                {
-                    luminance * 903.3
+                    if args.flip_vertical.is_none() && new_config.flipped_vertical != config.flipped_vertical
+                    {
+                        flipped_vertical = new_config.flipped_vertical;
+                    }
+
+                    if let Some(on_top) = new_config.always_on_top.filter(|_| new_config.always_on_top != config.always_on_top)
+                    {
+                        always_on_top = on_top;
+                        tx.send(ProgramMessage::SetAlwaysOnTop(on_top)).unwrap();
+                    }
+
+                    config = new_config;
+
+                    println!("reloaded config");
+                    flash = Some(("CONFIG RELOADED".to_owned(), Instant::now()));
                 } else
                 {
-                    luminance.cbrt() * 116.0 - 16.0
+                    eprintln!("keeping the last working config");
                 }
-            };
-
-            let brightness_diff = target_brightness - average_brightness;
+            }
+        }
 
-            if brightness_diff.abs() > brightness_range
+        if let Some(writer) = stats_log.as_mut()
+        {
+            if last_stats.elapsed() >= Duration::from_secs(1)
             {
-                let current_gamma = gamma_control.current();
-                let new_gamma = if brightness_diff < 0.0
-                {
-                    current_gamma - 1
-                } else
+                last_stats = Instant::now();
+
+                let timestamp = Local::now().format("%Y-%m-%dT%H:%M:%S%.3f");
+                let fps = 1000.0 / averager.average();
+
+                let gamma = software_gamma.as_ref().map(|gamma_lut|
                 {
-                    current_gamma + 1
-                };
+                    format!("{:.2}", gamma_lut.gamma())
+                }).unwrap_or_else(|| controls.gamma.current().to_string());
 
-                gamma_control.set(&mut camera, new_gamma);
+                let result = writeln!(writer, "{timestamp},{fps:.2},{gamma},{dropped_frames}")
+                    .and_then(|_| writer.flush());
+
+                if let Err(err) = result
+                {
+                    eprintln!("error writing stats: {err}");
+                }
             }
         }
 
-        let frametime = last_frame.elapsed().as_secs_f64() * 1000.0;
-        let current_average = averager.add(frametime);
+        if let Some(frame_budget) = frame_budget
+        {
+            let elapsed = last_frame.elapsed();
 
-        tx.send(ProgramMessage::Render(Box::new(image))).unwrap();
+            if elapsed < frame_budget
+            {
+                thread::sleep(frame_budget - elapsed);
+            }
+        }
 
-        title_delay -= 1;
-        if title_delay <= 0
+        if !paused
         {
-            let fps = 1000.0 / current_average;
-            let gamma = gamma_control.current();
+            let frametime = last_frame.elapsed().as_secs_f64() * 1000.0;
+            averager.add(frametime);
+        }
 
-            let gamma_tag = match gamma_mode
-            {
-                GammaMode::Auto => "AUTO",
-                GammaMode::Manual{fullbright: true, ..} => "FULLBRIGHT",
-                GammaMode::Manual{..} => ""
-            };
+        last_frame = Instant::now();
+        frame_index = frame_index.wrapping_add(1);
+    }
 
-            let gamma_tag = if gamma_tag.is_empty()
-            {
-                String::new()
-            } else
-            {
-                format!("[{gamma_tag}] ")
-            };
+    config.auto_gamma = gamma_mode == GammaMode::Auto;
 
-            let title = format!("{fps:.1} fps, {gamma_tag}{gamma} gamma");
+    if let GammaMode::Manual{current, ..} = gamma_mode
+    {
+        config.gamma = Some(current);
+    }
 
-            tx.send(ProgramMessage::SetTitle(title)).unwrap();
+    if save_mirrored
+    {
+        config.mirrored = mirrored;
+    }
 
-            title_delay = 10;
-        }
+    if save_flipped_vertical
+    {
+        config.flipped_vertical = flipped_vertical;
+    }
 
-        last_frame = Instant::now();
+    if save_fps
+    {
+        config.fps = requested_fps;
     }
 
-    gamma_control.reset(&mut camera);
-    brightness_control.reset(&mut camera);
+    config.always_on_top = Some(always_on_top);
+    config.window_position = *window_position.lock().unwrap();
+
+    config.save(config_camera);
+
+    capture_running.store(false, Ordering::Relaxed);
+    drop(frame_rx);
+
+    capture_thread.join().unwrap();
+
+    controls.reset(&mut shared_camera.lock().unwrap());
+
+    if let Some(frames) = recording.take()
+    {
+        if !frames.is_empty()
+        {
+            encoding_threads.push(save_recording("recording", frames, averager.average(), quality));
+        }
+    }
 
     drop(tx);
 
     input_thread.join().unwrap();
+
+    encoding_threads.into_iter().for_each(|handle| handle.join().unwrap());
 }
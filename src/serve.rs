@@ -0,0 +1,114 @@
+use std::{
+    thread,
+    io::{self, Read, Write},
+    net::{TcpListener, TcpStream},
+    time::Duration,
+    sync::{
+        Arc,
+        Mutex,
+        mpsc::{self, SyncSender}
+    }
+};
+
+use image::{RgbImage, codecs::jpeg::JpegEncoder};
+
+
+const BOUNDARY: &str = "frame";
+
+pub struct MjpegServer
+{
+    clients: Arc<Mutex<Vec<TcpStream>>>,
+    frames: SyncSender<RgbImage>
+}
+
+impl MjpegServer
+{
+    pub fn start(port: u16, quality: u8) -> io::Result<Self>
+    {
+        let listener = TcpListener::bind(("0.0.0.0", port))?;
+
+        let clients = Arc::new(Mutex::new(Vec::new()));
+
+        {
+            let clients = clients.clone();
+
+            thread::spawn(move ||
+            {
+                for stream in listener.incoming()
+                {
+                    match stream.and_then(accept_client)
+                    {
+                        Ok(stream) => clients.lock().unwrap().push(stream),
+                        Err(err) => eprintln!("error accepting a stream client: {err}")
+                    }
+                }
+            });
+        }
+
+        let (frames, frames_rx) = mpsc::sync_channel::<RgbImage>(1);
+
+        {
+            let clients = clients.clone();
+
+            thread::spawn(move ||
+            {
+                while let Ok(image) = frames_rx.recv()
+                {
+                    let mut data = Vec::new();
+                    if let Err(err) = JpegEncoder::new_with_quality(&mut data, quality).encode_image(&image)
+                    {
+                        eprintln!("error encoding a stream frame: {err}");
+                        continue;
+                    }
+
+                    let header = format!(
+                        "--{BOUNDARY}\r\nContent-Type: image/jpeg\r\nContent-Length: {}\r\n\r\n",
+                        data.len()
+                    );
+
+                    clients.lock().unwrap().retain_mut(|client|
+                    {
+                        client.write_all(header.as_bytes())
+                            .and_then(|_| client.write_all(&data))
+                            .and_then(|_| client.write_all(b"\r\n"))
+                            .is_ok()
+                    });
+                }
+            });
+        }
+
+        println!("streaming on port {port}");
+
+        Ok(Self{clients, frames})
+    }
+
+    pub fn has_clients(&self) -> bool
+    {
+        !self.clients.lock().unwrap().is_empty()
+    }
+
+    pub fn send(&self, image: &RgbImage)
+    {
+        if self.has_clients()
+        {
+            let _ = self.frames.try_send(image.clone());
+        }
+    }
+}
+
+fn accept_client(mut stream: TcpStream) -> io::Result<TcpStream>
+{
+    stream.set_read_timeout(Some(Duration::from_secs(1)))?;
+    stream.set_write_timeout(Some(Duration::from_secs(2)))?;
+
+    let mut request = [0; 1024];
+    let _ = stream.read(&mut request)?;
+
+    let header = format!(
+        "HTTP/1.0 200 OK\r\nCache-Control: no-cache\r\nContent-Type: multipart/x-mixed-replace; boundary={BOUNDARY}\r\n\r\n"
+    );
+
+    stream.write_all(header.as_bytes())?;
+
+    Ok(stream)
+}